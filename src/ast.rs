@@ -1,8 +1,281 @@
-mod pretty;
+pub mod dot;
+pub mod format;
+pub mod json;
+pub mod mut_visit;
+mod node_map;
+pub mod pretty;
+pub mod sexp;
+mod structural_eq;
+pub mod visit;
+
+pub use mut_visit::MutVisitor;
+pub use node_map::NodeMap;
+pub use structural_eq::StructuralEq;
+pub use visit::Visitor;
 
 use crate::{interner::Symbol, span::Span, token::Token};
 
-#[derive(Clone, Debug)]
+/// An owned pointer to an AST node.
+pub type P<T> = Box<T>;
+
+use std::{
+    hash::{Hash, Hasher},
+    mem,
+};
+
+/// Identifies a node of the AST, unlike its span which can be shared, e.g. by
+/// the params of a group. Ids are handed out in order while parsing, starting
+/// from 0. Identifiers and literals have no id of their own, since each of them
+/// belongs to a single node which has one (see DESIGN.md).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn new(index: u32) -> Self {
+        Self(index)
+    }
+
+    pub fn index(self) -> usize {
+        usize::try_from(self.0).unwrap()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+    /// `( ... )`
+    Paren,
+    /// `[ ... ]`
+    Bracket,
+}
+
+/// A delimited sequence of token trees, kept unparsed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DelimArgs {
+    pub span: Span,
+    pub delim: Delimiter,
+    pub tokens: Vec<TokenTree>,
+}
+
+impl DelimArgs {
+    pub fn new(span: Span, delim: Delimiter, tokens: Vec<TokenTree>) -> Self {
+        Self {
+            span,
+            delim,
+            tokens,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TokenTree {
+    Token(Token),
+    Delimited(DelimArgs),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttrStyle {
+    /// `#[attr]`, applying to the following item, field or variant
+    Outer,
+    /// `#![attr]`, applying to the enclosing module
+    Inner,
+}
+
+/// `#[path]` or `#[path(args)]`, and their `#!` inner counterparts.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Attribute {
+    pub id: NodeId,
+    pub span: Span,
+    pub style: AttrStyle,
+    pub path: Path,
+    pub args: Option<DelimArgs>,
+}
+
+impl Attribute {
+    pub fn new(
+        id: NodeId,
+        span: Span,
+        style: AttrStyle,
+        path: Path,
+        args: Option<DelimArgs>,
+    ) -> Self {
+        Self {
+            id,
+            span,
+            style,
+            path,
+            args,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IntTy {
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl IntTy {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "u8" => Some(IntTy::U8),
+            "u16" => Some(IntTy::U16),
+            "u32" => Some(IntTy::U32),
+            "u64" => Some(IntTy::U64),
+            "i8" => Some(IntTy::I8),
+            "i16" => Some(IntTy::I16),
+            "i32" => Some(IntTy::I32),
+            "i64" => Some(IntTy::I64),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            IntTy::U8 => "u8",
+            IntTy::U16 => "u16",
+            IntTy::U32 => "u32",
+            IntTy::U64 => "u64",
+            IntTy::I8 => "i8",
+            IntTy::I16 => "i16",
+            IntTy::I32 => "i32",
+            IntTy::I64 => "i64",
+        }
+    }
+
+    /// The largest magnitude a literal of this type can have, depending on
+    /// whether it is negated.
+    pub fn max_magnitude(self, negated: bool) -> u128 {
+        let bits = match self {
+            IntTy::U8 | IntTy::I8 => 8,
+            IntTy::U16 | IntTy::I16 => 16,
+            IntTy::U32 | IntTy::I32 => 32,
+            IntTy::U64 | IntTy::I64 => 64,
+        };
+        match self {
+            IntTy::U8 | IntTy::U16 | IntTy::U32 | IntTy::U64 if negated => 0,
+            IntTy::U8 | IntTy::U16 | IntTy::U32 | IntTy::U64 => (1 << bits) - 1,
+            _ if negated => 1 << (bits - 1),
+            _ => (1 << (bits - 1)) - 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FloatTy {
+    F32,
+    F64,
+}
+
+impl FloatTy {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "f32" => Some(FloatTy::F32),
+            "f64" => Some(FloatTy::F64),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            FloatTy::F32 => "f32",
+            FloatTy::F64 => "f64",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum LitKind {
+    /// `42`, `300u8`; the sign of negative literals is a separate `-`.
+    Int { value: u128, suffix: Option<IntTy> },
+    /// `1.5`, `2f32`
+    Float { value: f64, suffix: Option<FloatTy> },
+    /// `2.5i`
+    Complex { re: f64, im: f64 },
+    /// `'c'`, with escape sequences decoded
+    Char(char),
+    /// `"str"`, with escape sequences decoded
+    Str(Symbol),
+}
+
+// Floats are compared by their bits, so that `Eq` and `Hash` agree. Literals
+// are never negative or NaN, so this is the same as comparing their values.
+impl PartialEq for LitKind {
+    fn eq(&self, other: &Self) -> bool {
+        use LitKind::*;
+
+        match (*self, *other) {
+            (
+                Int { value, suffix },
+                Int {
+                    value: value2,
+                    suffix: suffix2,
+                },
+            ) => value == value2 && suffix == suffix2,
+            (
+                Float { value, suffix },
+                Float {
+                    value: value2,
+                    suffix: suffix2,
+                },
+            ) => value.to_bits() == value2.to_bits() && suffix == suffix2,
+            (Complex { re, im }, Complex { re: re2, im: im2 }) => {
+                re.to_bits() == re2.to_bits() && im.to_bits() == im2.to_bits()
+            }
+            (Char(c), Char(c2)) => c == c2,
+            (Str(s), Str(s2)) => s == s2,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for LitKind {}
+
+impl Hash for LitKind {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match *self {
+            LitKind::Int { value, suffix } => (value, suffix).hash(state),
+            LitKind::Float { value, suffix } => (value.to_bits(), suffix).hash(state),
+            LitKind::Complex { re, im } => (re.to_bits(), im.to_bits()).hash(state),
+            LitKind::Char(c) => c.hash(state),
+            LitKind::Str(s) => s.hash(state),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Lit {
+    pub span: Span,
+    pub kind: LitKind,
+}
+
+impl Lit {
+    pub fn new(span: Span, kind: LitKind) -> Self {
+        Self { span, kind }
+    }
+}
+
+/// Items, fields and methods are private to their module unless marked `pub`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    Private,
+    /// Holds the span of the `pub` keyword.
+    Public(Span),
+}
+
+impl Visibility {
+    pub fn is_public(self) -> bool {
+        matches!(self, Visibility::Public(_))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Ident {
     pub span: Span,
     pub name: Symbol,
@@ -14,128 +287,829 @@ impl Ident {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Path {
+    pub id: NodeId,
     pub span: Span,
     pub segments: Vec<Ident>,
 }
 
 impl Path {
-    pub fn new(span: Span, segments: Vec<Ident>) -> Self {
-        Self { span, segments }
+    pub fn new(id: NodeId, span: Span, segments: Vec<Ident>) -> Self {
+        Self { id, span, segments }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Tuple {
+    pub id: NodeId,
     pub span: Span,
     pub types: Vec<Ty>,
 }
 
 impl Tuple {
-    pub fn new(span: Span, types: Vec<Ty>) -> Self {
-        Self { span, types }
+    pub fn new(id: NodeId, span: Span, types: Vec<Ty>) -> Self {
+        Self { id, span, types }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Array {
+    pub id: NodeId,
     pub span: Span,
-    pub ty: Box<Ty>,
-    pub len: Token,
+    pub ty: P<Ty>,
+    /// Evaluated at compile time, see [`crate::const_eval`].
+    pub len: P<Expr>,
 }
 
 impl Array {
-    pub fn new(span: Span, ty: Box<Ty>, len: Token) -> Self {
-        Self { span, ty, len }
+    pub fn new(id: NodeId, span: Span, ty: P<Ty>, len: P<Expr>) -> Self {
+        Self { id, span, ty, len }
+    }
+}
+
+/// `param -> ret`, where `ret` may itself be a function type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FnTy {
+    pub id: NodeId,
+    pub span: Span,
+    pub param: P<Ty>,
+    pub ret: P<Ty>,
+}
+
+impl FnTy {
+    pub fn new(id: NodeId, span: Span, param: P<Ty>, ret: P<Ty>) -> Self {
+        Self {
+            id,
+            span,
+            param,
+            ret,
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TyKind {
     Path(Path),
     Tuple(Tuple),
     Array(Array),
+    Fn(FnTy),
+    /// `raw T`
+    RawPtr(P<Ty>),
+    /// `&T`, `&mut T`
+    Ref {
+        mutable: bool,
+        ty: P<Ty>,
+    },
+    /// `!`, used for functions without arguments or without return value.
+    Never,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Ty {
+    pub id: NodeId,
     pub span: Span,
     pub kind: TyKind,
 }
 
 impl Ty {
-    pub fn new(span: Span, kind: TyKind) -> Self {
-        Self { span, kind }
+    pub fn new(id: NodeId, span: Span, kind: TyKind) -> Self {
+        Self { id, span, kind }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Variant {
+    pub id: NodeId,
     pub span: Span,
+    pub attrs: Vec<Attribute>,
     pub name: Ident,
     pub data: Vec<Ty>,
 }
 
 impl Variant {
-    pub fn new(span: Span, name: Ident, data: Vec<Ty>) -> Self {
-        Self { span, name, data }
+    pub fn new(id: NodeId, span: Span, attrs: Vec<Attribute>, name: Ident, data: Vec<Ty>) -> Self {
+        Self {
+            id,
+            span,
+            attrs,
+            name,
+            data,
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Enum {
+    pub id: NodeId,
     pub span: Span,
     pub variants: Vec<Variant>,
 }
 
 impl Enum {
-    pub fn new(span: Span, variants: Vec<Variant>) -> Self {
-        Self { span, variants }
+    pub fn new(id: NodeId, span: Span, variants: Vec<Variant>) -> Self {
+        Self { id, span, variants }
     }
 }
 
-#[derive(Clone, Debug)]
+/// A single parameter or field name, whose type is given by its group.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Param {
+    pub id: NodeId,
     pub span: Span,
     pub name: Ident,
-    pub ty: Ty,
 }
 
 impl Param {
-    pub fn new(span: Span, name: Ident, ty: Ty) -> Self {
-        Self { span, name, ty }
+    pub fn new(id: NodeId, span: Span, name: Ident) -> Self {
+        Self { id, span, name }
     }
 }
 
-#[derive(Clone, Debug)]
+/// `(x y: f64)`: parameters or record fields declared together, sharing their
+/// type, visibility and attributes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParamGroup {
+    pub id: NodeId,
+    pub span: Span,
+    pub attrs: Vec<Attribute>,
+    /// Always private for parameters.
+    pub vis: Visibility,
+    pub params: Vec<Param>,
+    pub ty: Ty,
+}
+
+impl ParamGroup {
+    pub fn new(
+        id: NodeId,
+        span: Span,
+        attrs: Vec<Attribute>,
+        vis: Visibility,
+        params: Vec<Param>,
+        ty: Ty,
+    ) -> Self {
+        Self {
+            id,
+            span,
+            attrs,
+            vis,
+            params,
+            ty,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Record {
+    pub id: NodeId,
     pub span: Span,
-    pub fields: Vec<Param>,
+    pub fields: Vec<ParamGroup>,
 }
 
 impl Record {
-    pub fn new(span: Span, fields: Vec<Param>) -> Self {
-        Self { span, fields }
+    pub fn new(id: NodeId, span: Span, fields: Vec<ParamGroup>) -> Self {
+        Self { id, span, fields }
+    }
+
+    /// Iterates over the fields one by one, along with their group.
+    pub fn iter_fields(&self) -> impl Iterator<Item = (&Param, &ParamGroup)> {
+        self.fields
+            .iter()
+            .flat_map(|group| group.params.iter().map(move |param| (param, group)))
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TyDefKind {
     Alias(Ty),
     Record(Record),
     Enum(Enum),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TyDef {
+    pub id: NodeId,
     pub span: Span,
+    pub vis: Visibility,
     pub name: Ident,
     pub ty: TyDefKind,
 }
 
 impl TyDef {
-    pub fn new(span: Span, name: Ident, ty: TyDefKind) -> Self {
-        Self { span, name, ty }
+    pub fn new(id: NodeId, span: Span, vis: Visibility, name: Ident, ty: TyDefKind) -> Self {
+        Self {
+            id,
+            span,
+            vis,
+            name,
+            ty,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Binding {
+    pub id: NodeId,
+    pub span: Span,
+    pub mutable: bool,
+    pub name: Ident,
+}
+
+impl Binding {
+    pub fn new(id: NodeId, span: Span, mutable: bool, name: Ident) -> Self {
+        Self {
+            id,
+            span,
+            mutable,
+            name,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PatternKind {
+    /// `_`
+    Wild,
+    /// `x`, `mut x`
+    Binding(Binding),
+    /// `0.0`, `'c'`, `"str"`
+    Lit(Lit),
+    /// `-1`, `-2.5`
+    Neg(Lit),
+    /// `(p1, p2, ...)`
+    Tuple(Vec<Pattern>),
+    /// `[p1, p2, ...]`
+    Array(Vec<Pattern>),
+    /// Record constructors and enum variants: `Point x y`, `Shape~Circle c r`
+    Ctor(Path, Vec<Pattern>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: PatternKind,
+}
+
+impl Pattern {
+    pub fn new(id: NodeId, span: Span, kind: PatternKind) -> Self {
+        Self { id, span, kind }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UnOp {
+    /// `-e`
+    Neg,
+    /// `*e`
+    Deref,
+    /// `&e`
+    Ref,
+    /// `&mut e`
+    RefMut,
+    /// `raw e`
+    Raw,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    LtEq,
+    GtEq,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Let {
+    pub id: NodeId,
+    pub span: Span,
+    pub pat: Pattern,
+    pub ty: Option<Ty>,
+    pub init: Expr,
+}
+
+impl Let {
+    pub fn new(id: NodeId, span: Span, pat: Pattern, ty: Option<Ty>, init: Expr) -> Self {
+        Self {
+            id,
+            span,
+            pat,
+            ty,
+            init,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StmtKind {
+    /// `let pat : ty := init.`
+    Let(Let),
+    /// `expr.`, or a block-like expression such as `if ... end`
+    Expr(Expr),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Stmt {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: StmtKind,
+}
+
+impl Stmt {
+    pub fn new(id: NodeId, span: Span, kind: StmtKind) -> Self {
+        Self { id, span, kind }
+    }
+}
+
+/// A sequence of statements, optionally ending with an expression (without a
+/// trailing `.`) giving the value of the block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Block {
+    pub id: NodeId,
+    pub span: Span,
+    pub stmts: Vec<Stmt>,
+    pub expr: Option<P<Expr>>,
+}
+
+impl Block {
+    pub fn new(id: NodeId, span: Span, stmts: Vec<Stmt>, expr: Option<P<Expr>>) -> Self {
+        Self {
+            id,
+            span,
+            stmts,
+            expr,
+        }
+    }
+}
+
+/// `if cond then body` or `elif cond then body`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IfBranch {
+    pub id: NodeId,
+    pub span: Span,
+    pub cond: Expr,
+    pub body: Block,
+}
+
+impl IfBranch {
+    pub fn new(id: NodeId, span: Span, cond: Expr, body: Block) -> Self {
+        Self {
+            id,
+            span,
+            cond,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct If {
+    pub id: NodeId,
+    pub span: Span,
+    pub branches: Vec<IfBranch>,
+    pub else_branch: Option<Block>,
+}
+
+impl If {
+    pub fn new(
+        id: NodeId,
+        span: Span,
+        branches: Vec<IfBranch>,
+        else_branch: Option<Block>,
+    ) -> Self {
+        Self {
+            id,
+            span,
+            branches,
+            else_branch,
+        }
+    }
+}
+
+/// `| pat => body`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MatchArm {
+    pub id: NodeId,
+    pub span: Span,
+    pub pat: Pattern,
+    pub body: Expr,
+}
+
+impl MatchArm {
+    pub fn new(id: NodeId, span: Span, pat: Pattern, body: Expr) -> Self {
+        Self {
+            id,
+            span,
+            pat,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub id: NodeId,
+    pub span: Span,
+    pub scrutinee: P<Expr>,
+    pub arms: Vec<MatchArm>,
+}
+
+impl Match {
+    pub fn new(id: NodeId, span: Span, scrutinee: P<Expr>, arms: Vec<MatchArm>) -> Self {
+        Self {
+            id,
+            span,
+            scrutinee,
+            arms,
+        }
+    }
+}
+
+/// `| cond => body`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WhenArm {
+    pub id: NodeId,
+    pub span: Span,
+    pub cond: Expr,
+    pub body: Expr,
+}
+
+impl WhenArm {
+    pub fn new(id: NodeId, span: Span, cond: Expr, body: Expr) -> Self {
+        Self {
+            id,
+            span,
+            cond,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct When {
+    pub id: NodeId,
+    pub span: Span,
+    pub arms: Vec<WhenArm>,
+    pub else_branch: Option<Block>,
+}
+
+impl When {
+    pub fn new(id: NodeId, span: Span, arms: Vec<WhenArm>, else_branch: Option<Block>) -> Self {
+        Self {
+            id,
+            span,
+            arms,
+            else_branch,
+        }
+    }
+}
+
+/// `for pat in iter do body end`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct For {
+    pub id: NodeId,
+    pub span: Span,
+    pub pat: Pattern,
+    pub iter: P<Expr>,
+    pub body: Block,
+}
+
+impl For {
+    pub fn new(id: NodeId, span: Span, pat: Pattern, iter: P<Expr>, body: Block) -> Self {
+        Self {
+            id,
+            span,
+            pat,
+            iter,
+            body,
+        }
+    }
+}
+
+/// `(x y: T1) (z: T2) => body`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Lambda {
+    pub id: NodeId,
+    pub span: Span,
+    pub params: Vec<ParamGroup>,
+    pub body: P<Expr>,
+}
+
+impl Lambda {
+    pub fn new(id: NodeId, span: Span, params: Vec<ParamGroup>, body: P<Expr>) -> Self {
+        Self {
+            id,
+            span,
+            params,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExprKind {
+    Lit(Lit),
+    Path(Path),
+    /// `()`, `(e1, e2, ...)`
+    Tuple(Vec<Expr>),
+    /// `f a b`
+    Call(P<Expr>, Vec<Expr>),
+    Unary(UnOp, P<Expr>),
+    Binary(BinOp, P<Expr>, P<Expr>),
+    /// `place <- value`
+    Assign(P<Expr>, P<Expr>),
+    /// `e is pat`
+    Is(P<Expr>, Pattern),
+    /// `begin ... end`
+    Block(Block),
+    If(If),
+    Match(Match),
+    When(When),
+    /// `loop ... end`
+    Loop(Block),
+    For(For),
+    Break,
+    Continue,
+    Return(Option<P<Expr>>),
+    Lambda(Lambda),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Expr {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: ExprKind,
+}
+
+impl Expr {
+    pub fn new(id: NodeId, span: Span, kind: ExprKind) -> Self {
+        Self { id, span, kind }
+    }
+
+    /// Whether the expression is terminated by `end`, in which case it doesn't
+    /// need a trailing `.` when used as a statement.
+    pub fn is_block_like(&self) -> bool {
+        matches!(
+            self.kind,
+            ExprKind::Block(_)
+                | ExprKind::If(_)
+                | ExprKind::Match(_)
+                | ExprKind::When(_)
+                | ExprKind::Loop(_)
+                | ExprKind::For(_)
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReceiverKind {
+    /// `(self)`
+    Value,
+    /// `(mut self)`
+    MutValue,
+    /// `(&self)`
+    Ref,
+    /// `(&mut self)`
+    RefMut,
+}
+
+/// The `self` parameter of a method, which must come first.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Receiver {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: ReceiverKind,
+}
+
+impl Receiver {
+    pub fn new(id: NodeId, span: Span, kind: ReceiverKind) -> Self {
+        Self { id, span, kind }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FnDef {
+    pub id: NodeId,
+    pub span: Span,
+    pub vis: Visibility,
+    pub name: Ident,
+    /// Only methods defined in an `impl` block can have a receiver.
+    pub receiver: Option<Receiver>,
+    pub params: Vec<ParamGroup>,
+    pub ret: Option<Ty>,
+    /// Either a `begin ... end` block or the expression following `:=`.
+    pub body: Expr,
+}
+
+impl FnDef {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: NodeId,
+        span: Span,
+        vis: Visibility,
+        name: Ident,
+        receiver: Option<Receiver>,
+        params: Vec<ParamGroup>,
+        ret: Option<Ty>,
+        body: Expr,
+    ) -> Self {
+        Self {
+            id,
+            span,
+            vis,
+            name,
+            receiver,
+            params,
+            ret,
+            body,
+        }
+    }
+}
+
+/// `let NAME : ty := value.` at the item level.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConstDef {
+    pub id: NodeId,
+    pub span: Span,
+    pub vis: Visibility,
+    pub name: Ident,
+    pub ty: Option<Ty>,
+    pub value: Expr,
+}
+
+impl ConstDef {
+    pub fn new(
+        id: NodeId,
+        span: Span,
+        vis: Visibility,
+        name: Ident,
+        ty: Option<Ty>,
+        value: Expr,
+    ) -> Self {
+        Self {
+            id,
+            span,
+            vis,
+            name,
+            ty,
+            value,
+        }
+    }
+}
+
+/// A function defined in an `impl` block, with its outer attributes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Method {
+    pub id: NodeId,
+    pub span: Span,
+    pub attrs: Vec<Attribute>,
+    pub fn_def: FnDef,
+}
+
+impl Method {
+    pub fn new(id: NodeId, span: Span, attrs: Vec<Attribute>, fn_def: FnDef) -> Self {
+        Self {
+            id,
+            span,
+            attrs,
+            fn_def,
+        }
+    }
+}
+
+/// `impl Target fun ... end`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Impl {
+    pub id: NodeId,
+    pub span: Span,
+    pub target: Path,
+    pub methods: Vec<Method>,
+}
+
+impl Impl {
+    pub fn new(id: NodeId, span: Span, target: Path, methods: Vec<Method>) -> Self {
+        Self {
+            id,
+            span,
+            target,
+            methods,
+        }
+    }
+}
+
+/// `module A.` declares the file `A` as a submodule, `module A begin ... end`
+/// defines the submodule inline.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Module {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Ident,
+    /// Inner attributes at the start of an inline module, or of the file of
+    /// a loaded one.
+    pub attrs: Vec<Attribute>,
+    /// `None` for out-of-line modules, until their file is
+    /// [loaded](crate::load).
+    pub items: Option<Vec<Item>>,
+}
+
+impl Module {
+    pub fn new(
+        id: NodeId,
+        span: Span,
+        name: Ident,
+        attrs: Vec<Attribute>,
+        items: Option<Vec<Item>>,
+    ) -> Self {
+        Self {
+            id,
+            span,
+            name,
+            attrs,
+            items,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ImportKind {
+    /// `import A~B.` or `import A~B as C.`
+    Single(Option<Ident>),
+    /// `import A~*.`
+    Glob,
+}
+
+/// `import path.`, where `path` is absolute if it starts with `root`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Import {
+    pub id: NodeId,
+    pub span: Span,
+    pub path: Path,
+    pub kind: ImportKind,
+}
+
+impl Import {
+    pub fn new(id: NodeId, span: Span, path: Path, kind: ImportKind) -> Self {
+        Self {
+            id,
+            span,
+            path,
+            kind,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    TyDef(TyDef),
+    FnDef(FnDef),
+    ConstDef(ConstDef),
+    Impl(Impl),
+    Module(Module),
+    Import(Import),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Item {
+    pub id: NodeId,
+    pub span: Span,
+    pub attrs: Vec<Attribute>,
+    pub kind: ItemKind,
+}
+
+impl Item {
+    pub fn new(id: NodeId, span: Span, attrs: Vec<Attribute>, kind: ItemKind) -> Self {
+        Self {
+            id,
+            span,
+            attrs,
+            kind,
+        }
+    }
+}
+
+/// The contents of a source file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceFile {
+    pub id: NodeId,
+    pub span: Span,
+    /// Inner attributes at the start of the file.
+    pub attrs: Vec<Attribute>,
+    pub items: Vec<Item>,
+}
+
+impl SourceFile {
+    pub fn new(id: NodeId, span: Span, attrs: Vec<Attribute>, items: Vec<Item>) -> Self {
+        Self {
+            id,
+            span,
+            attrs,
+            items,
+        }
     }
 }
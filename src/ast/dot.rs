@@ -0,0 +1,170 @@
+//! Renders syntax trees as Graphviz DOT, e.g. `dot -Tsvg`.
+//!
+//! Trees are drawn from their [JSON form](super::json), so that anything
+//! exported as JSON, like annotated trees, can be drawn the same way:
+//!
+//! - Objects tagged with `"node"` become boxes labeled with the type of the
+//!   node, its id and where it lies as `line:col-line:col`. Identifiers are
+//!   shown as the name of the field holding them instead.
+//! - Fields holding nodes become edges labeled with the field's name, and
+//!   `field[i]` for the elements of arrays. Other fields are listed in the
+//!   label as `field = value`, except for `null` ones.
+//! - Enums show the name of their variant, and their fields are listed as
+//!   fields of the node holding them.
+
+use super::json::{to_json, ToJson};
+use crate::json::Json;
+
+use std::fmt::Write;
+
+/// Renders a node as a DOT graph. `src` is the source the node was parsed
+/// from, which gives the lines and columns of spans.
+pub fn to_dot<T: ToJson>(node: &T, src: &str) -> String {
+    json_to_dot(&to_json(node, src))
+}
+
+/// Renders the JSON form of a tree as a DOT graph.
+pub fn json_to_dot(json: &Json) -> String {
+    let mut graph = Graph::default();
+    graph.node(json);
+
+    let mut dot = String::from("digraph ast {\n    node [shape=box, fontname=monospace];\n");
+    dot.push_str(&graph.out);
+    dot.push_str("}\n");
+    dot
+}
+
+#[derive(Default)]
+struct Graph {
+    out: String,
+    nodes: usize,
+}
+
+/// What a node shows: lines of its label, and its children with their edge
+/// labels.
+#[derive(Default)]
+struct Fields<'a> {
+    lines: Vec<String>,
+    children: Vec<(String, &'a Json)>,
+}
+
+impl Graph {
+    /// Writes a node and its descendants, and returns the node's name.
+    fn node(&mut self, json: &Json) -> String {
+        let name = format!("n{}", self.nodes);
+        self.nodes += 1;
+
+        let mut title = json
+            .get("node")
+            .and_then(|node| node.as_str().ok())
+            .unwrap_or("?")
+            .to_string();
+        if let Some(Json::Number(id)) = json.get("id") {
+            write!(title, " #{id}").unwrap();
+        }
+        let mut label = vec![title];
+        if let Some(span) = json.get("span") {
+            label.push(span_text(span));
+        }
+
+        let mut fields = Fields::default();
+        if let Json::Object(entries) = json {
+            for (key, value) in entries {
+                if !matches!(key.as_str(), "node" | "id" | "span") {
+                    fields.add(key, value);
+                }
+            }
+        }
+        label.extend(fields.lines);
+
+        // `\l` ends left-justified lines
+        let label: String = label.iter().map(|line| escape(line) + "\\l").collect();
+        writeln!(self.out, "    {name} [label=\"{label}\"];").unwrap();
+        for (edge, child) in fields.children {
+            let child = self.node(child);
+            writeln!(
+                self.out,
+                "    {name} -> {child} [label=\"{}\"];",
+                escape(&edge)
+            )
+            .unwrap();
+        }
+        name
+    }
+}
+
+impl<'a> Fields<'a> {
+    fn add(&mut self, key: &str, value: &'a Json) {
+        match value {
+            Json::Null => {}
+            Json::Bool(b) => self.lines.push(format!("{key} = {b}")),
+            Json::Number(n) => self.lines.push(format!("{key} = {n}")),
+            Json::String(s) => self.lines.push(format!("{key} = {s}")),
+            Json::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.add(&format!("{key}[{i}]"), value);
+                }
+            }
+            Json::Object(_) => match (value.get("node"), value.get("variant")) {
+                (Some(Json::String(node)), _) if node == "Ident" => {
+                    if let Some(Json::String(name)) = value.get("name") {
+                        self.lines.push(format!("{key} = {name}"));
+                    }
+                }
+                (Some(_), _) => self.children.push((key.to_string(), value)),
+                (None, Some(Json::String(variant))) => {
+                    self.lines.push(format!("{key} = {variant}"));
+                    self.add_inner(key, value, "variant");
+                }
+                // tokens
+                (None, _) => match value.get("text") {
+                    Some(Json::String(text)) => self.lines.push(format!("{key} = {text}")),
+                    _ => self.add_inner(key, value, ""),
+                },
+            },
+        }
+    }
+
+    /// Lists the fields of an enum or another object without a node. Their
+    /// spans are left out. Fields of array elements keep the element's name
+    /// as prefix.
+    fn add_inner(&mut self, key: &str, value: &'a Json, tag: &str) {
+        let Json::Object(entries) = value else {
+            return;
+        };
+        for (inner, value) in entries {
+            if inner == tag || inner == "span" {
+                continue;
+            }
+            if key.ends_with(']') {
+                self.add(&format!("{key}.{inner}"), value);
+            } else {
+                self.add(inner, value);
+            }
+        }
+    }
+}
+
+fn span_text(span: &Json) -> String {
+    let get = |key| span.get(key).and_then(|n| n.as_u32().ok()).unwrap_or(0);
+    format!(
+        "{}:{}-{}:{}",
+        get("line"),
+        get("col"),
+        get("end_line"),
+        get("end_col")
+    )
+}
+
+fn escape(label: &str) -> String {
+    let mut out = String::new();
+    for c in label.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
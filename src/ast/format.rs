@@ -0,0 +1,916 @@
+//! Prints the AST as melange source, in a canonical layout:
+//!
+//! - Blocks are indented by four spaces between their opening keyword and
+//!   `end`, and fit on one line when they hold a single statement.
+//! - The `|` of variants and arms are aligned, on a line each, below their
+//!   type, `match` or `when`.
+//! - Grouped fields and parameters stay grouped, and records are broken
+//!   after each group when they don't fit on a line.
+//! - Long expressions are broken before their operators and arguments.
+//!
+//! Comments are kept: those before items, statements, arms, variants and
+//! fields are put on lines before them, and those ending the line of one of
+//! them stay at the end of its line. Other comments are moved to the next of
+//! these places. Single empty lines between them are kept too.
+//!
+//! Formatting the output again gives it back unchanged.
+
+use crate::{
+    doc::Doc,
+    interner,
+    lexer::Scanner,
+    parser::{self, parse_str},
+    span::Span,
+    token::{Kind, Token},
+};
+
+use super::*;
+
+/// The width lines are broken to by default.
+pub const WIDTH: usize = 80;
+
+const INDENT: usize = 4;
+
+/// Parses and formats a source file.
+pub fn format_source(src: &str, width: usize) -> parser::Result<String> {
+    let file: SourceFile = parse_str(src)?;
+    Ok(format(&file, src, width))
+}
+
+/// Formats a file parsed from `src`, which holds its comments.
+pub fn format(file: &SourceFile, src: &str, width: usize) -> String {
+    Formatter::new(src).source_file(file).render(width)
+}
+
+/// How tightly an expression binds, from the loosest.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Prec {
+    /// Assignments, and expressions which extend as far right as they can.
+    Expr,
+    Comparison,
+    Is,
+    Additive,
+    Multiplicative,
+    Unary,
+    Call,
+    Atom,
+}
+
+fn prec(expr: &Expr) -> Prec {
+    match &expr.kind {
+        ExprKind::Assign(..) | ExprKind::Lambda(_) | ExprKind::Return(_) => Prec::Expr,
+        ExprKind::Binary(BinOp::Add | BinOp::Sub, ..) => Prec::Additive,
+        ExprKind::Binary(BinOp::Mul | BinOp::Div, ..) => Prec::Multiplicative,
+        ExprKind::Binary(..) => Prec::Comparison,
+        ExprKind::Is(..) => Prec::Is,
+        ExprKind::Unary(..) => Prec::Unary,
+        ExprKind::Call(..) => Prec::Call,
+        _ => Prec::Atom,
+    }
+}
+
+fn bin_op(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Add => "+",
+        BinOp::Sub => "-",
+        BinOp::Mul => "*",
+        BinOp::Div => "/",
+        BinOp::Eq => "=",
+        BinOp::Neq => "/=",
+        BinOp::Lt => "<",
+        BinOp::Gt => ">",
+        BinOp::LtEq => "<=",
+        BinOp::GtEq => ">=",
+    }
+}
+
+/// Whether a block-like expression statement can go without its `.` when
+/// followed by `next`, which would otherwise continue the expression, e.g.
+/// as an argument.
+fn can_follow_block(next: &Stmt) -> bool {
+    match &next.kind {
+        StmtKind::Let(_) => true,
+        StmtKind::Expr(expr) => starts_statement(expr),
+    }
+}
+
+fn starts_statement(expr: &Expr) -> bool {
+    expr.is_block_like()
+        || matches!(
+            expr.kind,
+            ExprKind::Break
+                | ExprKind::Continue
+                | ExprKind::Return(_)
+                | ExprKind::Unary(UnOp::Ref | UnOp::RefMut | UnOp::Raw, _)
+        )
+}
+
+/// Whether an expression is printed right after `:=`, `<-` or `=>` rather
+/// than broken onto the next line, as it ends with a block.
+fn hugs(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Lambda(lambda) => hugs(&lambda.body),
+        _ => expr.is_block_like(),
+    }
+}
+
+/// Quotes the text of a char or string literal, escaping what needs to be.
+fn quote(text: &str, delim: char) -> String {
+    let mut quoted = String::from(delim);
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            c if c == delim => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push(delim);
+    quoted
+}
+
+fn lit(lit: &Lit) -> Doc {
+    Doc::Text(match lit.kind {
+        LitKind::Int { value, suffix } => format!("{value}{}", suffix.map_or("", IntTy::suffix)),
+        LitKind::Float { value, suffix } => {
+            let mut text = value.to_string();
+            // `3` would be an integer
+            if suffix.is_none() && !text.contains('.') {
+                text.push_str(".0");
+            }
+            text + suffix.map_or("", FloatTy::suffix)
+        }
+        LitKind::Complex { im, .. } => format!("{im}i"),
+        LitKind::Char(c) => quote(&c.to_string(), '\''),
+        LitKind::Str(s) => quote(interner::get(s), '"'),
+    })
+}
+
+fn ident(ident: &Ident) -> Doc {
+    Doc::text(interner::get(ident.name))
+}
+
+fn path(path: &Path) -> Doc {
+    let segments = path.segments.iter().map(|s| interner::get(s.name));
+    Doc::Text(segments.collect::<Vec<_>>().join("~"))
+}
+
+fn vis(vis: Visibility) -> Doc {
+    match vis {
+        Visibility::Public(_) => Doc::text("pub "),
+        Visibility::Private => Doc::Nil,
+    }
+}
+
+fn token(token: Token) -> String {
+    // string and char tokens hold their text without quotes
+    match token.kind() {
+        Kind::StrLit => format!("\"{}\"", token.repr()),
+        Kind::CharLit => format!("'{}'", token.repr()),
+        _ => token.repr().to_string(),
+    }
+}
+
+fn delim_args(args: &DelimArgs) -> String {
+    let (open, close) = match args.delim {
+        Delimiter::Paren => ('(', ')'),
+        Delimiter::Bracket => ('[', ']'),
+    };
+    let mut text = String::from(open);
+    let mut prev = None;
+    for tree in &args.tokens {
+        let (tree, kind) = match tree {
+            TokenTree::Token(t) => (token(*t), Some(t.kind())),
+            TokenTree::Delimited(args) => (delim_args(args), None),
+        };
+        // `a, b`, `A~B` and `f(x)`
+        let glued = matches!(
+            kind,
+            Some(Kind::Comma | Kind::Semi | Kind::Dot | Kind::Tilde)
+        ) || matches!(prev, Some(Some(Kind::Tilde)))
+            || (kind.is_none() && matches!(prev, Some(Some(Kind::Ident))));
+        if prev.is_some() && !glued {
+            text.push(' ');
+        }
+        text.push_str(&tree);
+        prev = Some(kind);
+    }
+    text.push(close);
+    text
+}
+
+fn attribute(attr: &Attribute) -> Doc {
+    let open = match attr.style {
+        AttrStyle::Outer => "#[",
+        AttrStyle::Inner => "#![",
+    };
+    let args = attr.args.as_ref().map_or(String::new(), delim_args);
+    Doc::Concat(vec![
+        Doc::text(open),
+        path(&attr.path),
+        Doc::Text(args),
+        Doc::text("]"),
+    ])
+}
+
+/// Attributes on the same line as what they apply to.
+fn inline_attrs(attrs: &[Attribute]) -> Doc {
+    Doc::concat(
+        attrs
+            .iter()
+            .map(|attr| attribute(attr).append(Doc::text(" "))),
+    )
+}
+
+fn receiver(receiver: &Receiver) -> Doc {
+    Doc::text(match receiver.kind {
+        ReceiverKind::Value => "(self)",
+        ReceiverKind::MutValue => "(mut self)",
+        ReceiverKind::Ref => "(&self)",
+        ReceiverKind::RefMut => "(&mut self)",
+    })
+}
+
+fn pattern(pat: &Pattern) -> Doc {
+    match &pat.kind {
+        PatternKind::Wild => Doc::text("_"),
+        PatternKind::Binding(binding) if binding.mutable => {
+            Doc::text("mut ").append(ident(&binding.name))
+        }
+        PatternKind::Binding(binding) => ident(&binding.name),
+        PatternKind::Lit(l) => lit(l),
+        PatternKind::Neg(l) => Doc::text("-").append(lit(l)),
+        PatternKind::Tuple(pats) => Doc::Concat(vec![
+            Doc::text("("),
+            Doc::join(pats.iter().map(pattern), Doc::text(", ")),
+            Doc::text(")"),
+        ]),
+        PatternKind::Array(pats) => Doc::Concat(vec![
+            Doc::text("["),
+            Doc::join(pats.iter().map(pattern), Doc::text(", ")),
+            Doc::text("]"),
+        ]),
+        PatternKind::Ctor(p, args) => {
+            let mut doc = vec![path(p)];
+            for arg in args {
+                doc.push(Doc::text(" "));
+                doc.push(match &arg.kind {
+                    PatternKind::Binding(Binding { mutable: true, .. }) => {
+                        Doc::Concat(vec![Doc::text("("), pattern(arg), Doc::text(")")])
+                    }
+                    PatternKind::Ctor(_, args) if !args.is_empty() => {
+                        Doc::Concat(vec![Doc::text("("), pattern(arg), Doc::text(")")])
+                    }
+                    _ => pattern(arg),
+                });
+            }
+            Doc::Concat(doc)
+        }
+    }
+}
+
+/// `entries` on their own indented lines, followed by a newline.
+fn hard_block(entries: Vec<Doc>) -> Doc {
+    if entries.is_empty() {
+        return Doc::text(" ");
+    }
+    let lines = entries.into_iter().flat_map(|entry| [Doc::HardLine, entry]);
+    Doc::concat(lines).nest(INDENT).append(Doc::HardLine)
+}
+
+/// `entries` on their own indented lines, or all on one line in a group
+/// which fits and holds at most one entry.
+fn lines(entries: Vec<Doc>) -> Doc {
+    let breaks = if entries.len() > 1 {
+        Doc::BreakParent
+    } else {
+        Doc::Nil
+    };
+    let lines = entries.into_iter().flat_map(|entry| [Doc::Line, entry]);
+    Doc::Concat(vec![breaks, Doc::concat(lines).nest(INDENT)])
+}
+
+/// [`lines`] followed by a newline.
+fn soft_block(entries: Vec<Doc>) -> Doc {
+    lines(entries).append(Doc::Line)
+}
+
+/// The elements of a list of items, statements, arms, variants or fields,
+/// each with the comments which precede it.
+#[derive(Default)]
+struct List {
+    entries: Vec<Doc>,
+    /// Where the last element or comment ends in the source.
+    prev: Option<u32>,
+}
+
+/// Prints an AST along with the comments of its source, which it goes
+/// through in order.
+struct Formatter<'src> {
+    src: &'src str,
+    comments: Vec<Span>,
+    /// The next comment to print.
+    next: usize,
+}
+
+impl<'src> Formatter<'src> {
+    fn new(src: &'src str) -> Self {
+        let mut scanner = Scanner::new(src).with_trivia();
+        let mut comments = Vec::new();
+        while let Some(token) = scanner.scan() {
+            if token.kind() == Kind::Comment {
+                comments.push(token.span());
+            }
+        }
+        Self {
+            src,
+            comments,
+            next: 0,
+        }
+    }
+
+    fn comment(&self, span: Span) -> &'src str {
+        self.src[span.start() as usize..span.end() as usize].trim_end()
+    }
+
+    /// Whether an empty line separates two offsets in the source.
+    fn blank_between(&self, start: u32, end: u32) -> bool {
+        self.src
+            .get(start as usize..end as usize)
+            .is_some_and(|between| between.matches('\n').count() > 1)
+    }
+
+    /// Takes the comments starting before `offset`, preceded by a newline
+    /// where they follow an empty line.
+    fn comments_before(&mut self, offset: u32, list: &mut List) -> Vec<Doc> {
+        let mut comments = Vec::new();
+        while let Some(&span) = self.comments.get(self.next) {
+            if span.start() >= offset {
+                break;
+            }
+            self.next += 1;
+            let blank = list
+                .prev
+                .is_some_and(|prev| self.blank_between(prev, span.start()));
+            comments.push(Doc::Concat(vec![
+                if blank { Doc::HardLine } else { Doc::Nil },
+                Doc::text(self.comment(span)),
+            ]));
+            list.prev = Some(span.end());
+        }
+        comments
+    }
+
+    /// Takes the comment following `offset` on the same line, if any.
+    fn trailing_comment(&mut self, offset: u32) -> Doc {
+        let Some(&span) = self.comments.get(self.next) else {
+            return Doc::Nil;
+        };
+        if span.start() < offset || self.src[offset as usize..span.start() as usize].contains('\n')
+        {
+            return Doc::Nil;
+        }
+        self.next += 1;
+        Doc::Concat(vec![
+            Doc::LineSuffix(format!(" {}", self.comment(span))),
+            Doc::BreakParent,
+        ])
+    }
+
+    /// Adds an element spanning `span` to a list.
+    fn element(&mut self, list: &mut List, span: Span, print: impl FnOnce(&mut Self) -> Doc) {
+        let mut entry: Vec<Doc> = self
+            .comments_before(span.start(), list)
+            .into_iter()
+            .flat_map(|comment| [comment, Doc::HardLine])
+            .collect();
+        if list
+            .prev
+            .is_some_and(|prev| self.blank_between(prev, span.start()))
+        {
+            entry.push(Doc::HardLine);
+        }
+        entry.push(print(self));
+        entry.push(self.trailing_comment(span.end()));
+        list.prev = Some(span.end());
+        list.entries.push(Doc::Concat(entry));
+    }
+
+    /// The entries of a list which ends at `end` in the source, the comments
+    /// left before it making up the last entry.
+    fn finish(&mut self, mut list: List, end: u32) -> Vec<Doc> {
+        let comments = self.comments_before(end, &mut list);
+        if !comments.is_empty() {
+            list.entries.push(Doc::join(comments, Doc::HardLine));
+        }
+        list.entries
+    }
+
+    fn ty(&mut self, ty: &Ty) -> Doc {
+        match &ty.kind {
+            TyKind::Path(p) => path(p),
+            TyKind::Tuple(tuple) => self.tuple_ty(&tuple.types),
+            TyKind::Array(array) => Doc::Concat(vec![
+                Doc::text("["),
+                self.ty(&array.ty),
+                Doc::text("; "),
+                self.expr(&array.len),
+                Doc::text("]"),
+            ]),
+            TyKind::Fn(fn_ty) => Doc::Concat(vec![
+                self.ty_operand(&fn_ty.param),
+                Doc::text(" -> "),
+                self.ty(&fn_ty.ret),
+            ]),
+            TyKind::RawPtr(ty) => Doc::text("raw ").append(self.ty_operand(ty)),
+            TyKind::Ref { mutable, ty } => {
+                Doc::text(if *mutable { "&mut " } else { "&" }).append(self.ty_operand(ty))
+            }
+            TyKind::Never => Doc::text("!"),
+        }
+    }
+
+    /// A type without arrows outside of parentheses.
+    fn ty_operand(&mut self, operand: &Ty) -> Doc {
+        match operand.kind {
+            TyKind::Fn(_) => Doc::Concat(vec![Doc::text("("), self.ty(operand), Doc::text(")")]),
+            _ => self.ty(operand),
+        }
+    }
+
+    fn tuple_ty(&mut self, types: &[Ty]) -> Doc {
+        let types: Vec<_> = types.iter().map(|t| self.ty(t)).collect();
+        Doc::Concat(vec![
+            Doc::text("("),
+            Doc::join(types, Doc::text(", ")),
+            Doc::text(")"),
+        ])
+    }
+
+    fn param_group(&mut self, group: &ParamGroup) -> Doc {
+        let names = group.params.iter().map(|param| ident(&param.name));
+        Doc::Concat(vec![
+            inline_attrs(&group.attrs),
+            Doc::text("("),
+            vis(group.vis),
+            Doc::join(names, Doc::text(" ")),
+            Doc::text(": "),
+            self.ty(&group.ty),
+            Doc::text(")"),
+        ])
+    }
+
+    fn source_file(&mut self, file: &SourceFile) -> Doc {
+        let mut list = List::default();
+        for attr in &file.attrs {
+            self.element(&mut list, attr.span, |_| attribute(attr));
+        }
+        for item in &file.items {
+            self.element(&mut list, item.span, |f| f.item(item));
+        }
+        let entries = self.finish(list, self.src.len() as u32);
+        if entries.is_empty() {
+            return Doc::Nil;
+        }
+        Doc::join(entries, Doc::HardLine).append(Doc::HardLine)
+    }
+
+    fn item(&mut self, item: &Item) -> Doc {
+        let mut doc: Vec<Doc> = item
+            .attrs
+            .iter()
+            .flat_map(|attr| [attribute(attr), Doc::HardLine])
+            .collect();
+        doc.push(match &item.kind {
+            ItemKind::TyDef(def) => self.ty_def(def),
+            ItemKind::FnDef(def) => self.fn_def(def),
+            ItemKind::ConstDef(def) => Doc::Concat(vec![
+                vis(def.vis),
+                Doc::text("let "),
+                ident(&def.name),
+                def.ty
+                    .as_ref()
+                    .map_or(Doc::Nil, |t| Doc::text(" : ").append(self.ty(t))),
+                Doc::text(" :="),
+                self.next_line(&def.value),
+                Doc::text("."),
+            ]),
+            ItemKind::Impl(imp) => self.impl_block(imp),
+            ItemKind::Module(module) => self.module(module),
+            ItemKind::Import(import) => Doc::Concat(vec![
+                Doc::text("import "),
+                path(&import.path),
+                match &import.kind {
+                    ImportKind::Single(None) => Doc::Nil,
+                    ImportKind::Single(Some(rename)) => Doc::text(" as ").append(ident(rename)),
+                    ImportKind::Glob => Doc::text("~*"),
+                },
+                Doc::text("."),
+            ]),
+        });
+        Doc::Concat(doc)
+    }
+
+    fn ty_def(&mut self, def: &TyDef) -> Doc {
+        let head = Doc::Concat(vec![
+            vis(def.vis),
+            Doc::text("type "),
+            ident(&def.name),
+            Doc::text(" :="),
+        ]);
+        let body = match &def.ty {
+            TyDefKind::Alias(alias) => Doc::Line.append(self.ty(alias)).nest(INDENT),
+            TyDefKind::Record(record) => {
+                let mut list = List::default();
+                for group in &record.fields {
+                    self.element(&mut list, group.span, |f| f.param_group(group));
+                }
+                let fields = self.finish(list, record.span.end());
+                let lines = fields.into_iter().flat_map(|field| [Doc::Line, field]);
+                Doc::text(" record").append(Doc::concat(lines).nest(INDENT))
+            }
+            TyDefKind::Enum(enumeration) => {
+                let mut list = List::default();
+                for variant in &enumeration.variants {
+                    self.element(&mut list, variant.span, |f| {
+                        Doc::Concat(vec![
+                            Doc::text("| "),
+                            inline_attrs(&variant.attrs),
+                            ident(&variant.name),
+                            if variant.data.is_empty() {
+                                Doc::Nil
+                            } else {
+                                Doc::text(" ").append(f.tuple_ty(&variant.data))
+                            },
+                        ])
+                    });
+                }
+                lines(self.finish(list, enumeration.span.end()))
+            }
+        };
+        Doc::Concat(vec![head, body]).group().append(Doc::text("."))
+    }
+
+    fn fn_def(&mut self, def: &FnDef) -> Doc {
+        let mut params = Vec::new();
+        if let Some(recv) = &def.receiver {
+            params.extend([Doc::Line, receiver(recv)]);
+        }
+        for group in &def.params {
+            params.extend([Doc::Line, self.param_group(group)]);
+        }
+        let signature = Doc::Concat(vec![
+            vis(def.vis),
+            Doc::text("fun "),
+            ident(&def.name),
+            Doc::Concat(params).nest(INDENT),
+        ])
+        .group();
+        let ret = def
+            .ret
+            .as_ref()
+            .map_or(Doc::Nil, |ret| Doc::text(" : ").append(self.ty(ret)));
+
+        match &def.body.kind {
+            ExprKind::Block(block) => {
+                let stmts = self.block(block, def.body.span.end() - 3);
+                Doc::Concat(vec![
+                    signature,
+                    ret,
+                    Doc::text(" begin"),
+                    hard_block(stmts),
+                    Doc::text("end"),
+                ])
+            }
+            _ => Doc::Concat(vec![
+                Doc::Concat(vec![
+                    signature,
+                    ret,
+                    Doc::text(" :="),
+                    self.next_line(&def.body),
+                ])
+                .group(),
+                Doc::text("."),
+            ]),
+        }
+    }
+
+    fn impl_block(&mut self, imp: &Impl) -> Doc {
+        let mut list = List::default();
+        for method in &imp.methods {
+            self.element(&mut list, method.span, |f| {
+                let mut doc: Vec<Doc> = method
+                    .attrs
+                    .iter()
+                    .flat_map(|attr| [attribute(attr), Doc::HardLine])
+                    .collect();
+                doc.push(f.fn_def(&method.fn_def));
+                Doc::Concat(doc)
+            });
+        }
+        let methods = self.finish(list, imp.span.end() - 3);
+        Doc::Concat(vec![
+            Doc::text("impl "),
+            path(&imp.target),
+            hard_block(methods),
+            Doc::text("end"),
+        ])
+    }
+
+    fn module(&mut self, module: &Module) -> Doc {
+        let head = Doc::text("module ").append(ident(&module.name));
+        let Some(items) = &module.items else {
+            return head.append(Doc::text("."));
+        };
+        let mut list = List::default();
+        for attr in &module.attrs {
+            self.element(&mut list, attr.span, |_| attribute(attr));
+        }
+        for item in items {
+            self.element(&mut list, item.span, |f| f.item(item));
+        }
+        let items = self.finish(list, module.span.end() - 3);
+        Doc::Concat(vec![
+            head,
+            Doc::text(" begin"),
+            hard_block(items),
+            Doc::text("end"),
+        ])
+    }
+
+    /// The statements of a block followed by its expression, the block
+    /// ending at `end` in the source.
+    fn block(&mut self, block: &Block, end: u32) -> Vec<Doc> {
+        let mut list = List::default();
+        for (i, stmt) in block.stmts.iter().enumerate() {
+            // without its `.`, a block-like statement would be the value of
+            // the block or be continued by what follows
+            let dot = match (block.stmts.get(i + 1), &block.expr) {
+                (Some(next), _) => !can_follow_block(next),
+                (None, Some(expr)) => !starts_statement(expr),
+                (None, None) => true,
+            };
+            self.element(&mut list, stmt.span, |f| f.stmt(stmt, dot));
+        }
+        if let Some(expr) = &block.expr {
+            self.element(&mut list, expr.span, |f| f.expr(expr));
+        }
+        self.finish(list, end)
+    }
+
+    /// Prints a statement, with a `.` after block-like expressions only if
+    /// `dot` is set.
+    fn stmt(&mut self, stmt: &Stmt, dot: bool) -> Doc {
+        match &stmt.kind {
+            StmtKind::Let(decl) => Doc::Concat(vec![
+                Doc::text("let "),
+                pattern(&decl.pat),
+                decl.ty
+                    .as_ref()
+                    .map_or(Doc::Nil, |t| Doc::text(" : ").append(self.ty(t))),
+                Doc::text(" :="),
+                self.rhs(&decl.init),
+                Doc::text("."),
+            ]),
+            StmtKind::Expr(expr) if expr.is_block_like() && !dot => self.expr(expr),
+            StmtKind::Expr(expr) => self.expr(expr).append(Doc::text(".")),
+        }
+    }
+
+    /// The expression following `:=`, `<-` or `=>`, which goes on the next
+    /// line if it doesn't fit, unless it ends with a block.
+    fn rhs(&mut self, expr: &Expr) -> Doc {
+        if hugs(expr) {
+            Doc::text(" ").append(self.expr(expr))
+        } else {
+            self.next_line(expr)
+        }
+    }
+
+    /// An expression on the next line, indented, if it doesn't fit on this
+    /// one.
+    fn next_line(&mut self, expr: &Expr) -> Doc {
+        Doc::Line.append(self.expr(expr)).nest(INDENT).group()
+    }
+
+    /// Prints an expression, in parentheses if it binds looser than `min`.
+    fn operand(&mut self, expr: &Expr, min: Prec) -> Doc {
+        let doc = self.expr(expr);
+        if prec(expr) < min {
+            Doc::Concat(vec![Doc::text("("), doc, Doc::text(")")])
+        } else {
+            doc
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Doc {
+        match &expr.kind {
+            ExprKind::Lit(l) => lit(l),
+            ExprKind::Path(p) => path(p),
+            ExprKind::Tuple(exprs) => {
+                let exprs: Vec<_> = exprs.iter().map(|e| self.expr(e)).collect();
+                Doc::Concat(vec![
+                    Doc::text("("),
+                    Doc::SoftLine
+                        .append(Doc::join(exprs, Doc::text(",").append(Doc::Line)))
+                        .nest(INDENT),
+                    Doc::SoftLine,
+                    Doc::text(")"),
+                ])
+                .group()
+            }
+            ExprKind::Call(callee, args) => {
+                let mut doc = vec![self.operand(callee, Prec::Atom)];
+                let mut rest = Vec::new();
+                for arg in args {
+                    // arguments start with an identifier, a literal or `(`
+                    let arg = match arg.kind {
+                        ExprKind::Lit(_) | ExprKind::Path(_) | ExprKind::Tuple(_) => self.expr(arg),
+                        _ => Doc::Concat(vec![Doc::text("("), self.expr(arg), Doc::text(")")]),
+                    };
+                    rest.extend([Doc::Line, arg]);
+                }
+                doc.push(Doc::Concat(rest).nest(INDENT));
+                Doc::Concat(doc).group()
+            }
+            ExprKind::Unary(op, operand) => Doc::text(match op {
+                UnOp::Neg => "-",
+                UnOp::Deref => "*",
+                UnOp::Ref => "&",
+                UnOp::RefMut => "&mut ",
+                UnOp::Raw => "raw ",
+            })
+            .append(self.operand(operand, Prec::Unary)),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let level = prec(expr);
+                // the operands of a chain of `+` or `*` are broken alike
+                let mut chain = vec![(*op, rhs)];
+                let mut first = lhs;
+                while let ExprKind::Binary(op, lhs, rhs) = &first.kind {
+                    if level == Prec::Comparison || prec(first) != level {
+                        break;
+                    }
+                    chain.push((*op, rhs));
+                    first = lhs;
+                }
+                let (lhs_min, rhs_min) = match level {
+                    Prec::Additive => (Prec::Additive, Prec::Multiplicative),
+                    Prec::Multiplicative => (Prec::Multiplicative, Prec::Unary),
+                    _ => (Prec::Is, Prec::Is),
+                };
+                let mut doc = vec![self.operand(first, lhs_min)];
+                for (op, rhs) in chain.into_iter().rev() {
+                    doc.extend([
+                        Doc::Line,
+                        Doc::text(bin_op(op)),
+                        Doc::text(" "),
+                        self.operand(rhs, rhs_min).nest(INDENT),
+                    ]);
+                }
+                Doc::Concat(doc).group()
+            }
+            ExprKind::Assign(place, value) => Doc::Concat(vec![
+                self.operand(place, Prec::Comparison),
+                Doc::text(" <-"),
+                self.rhs(value),
+            ]),
+            ExprKind::Is(expr, pat) => Doc::Concat(vec![
+                self.operand(expr, Prec::Additive),
+                Doc::text(" is "),
+                pattern(pat),
+            ]),
+            ExprKind::Block(block) => {
+                let stmts = self.block(block, expr.span.end() - 3);
+                Doc::Concat(vec![
+                    Doc::text("begin"),
+                    soft_block(stmts),
+                    Doc::text("end"),
+                ])
+                .group()
+            }
+            ExprKind::If(if_expr) => self.if_expr(if_expr),
+            ExprKind::Match(match_expr) => self.match_expr(match_expr),
+            ExprKind::When(when) => self.when(when),
+            ExprKind::Loop(body) => {
+                let stmts = self.block(body, expr.span.end() - 3);
+                Doc::Concat(vec![Doc::text("loop"), soft_block(stmts), Doc::text("end")]).group()
+            }
+            ExprKind::For(for_expr) => {
+                let head = Doc::Concat(vec![
+                    Doc::text("for "),
+                    pattern(&for_expr.pat),
+                    Doc::text(" in "),
+                    self.expr(&for_expr.iter).nest(INDENT),
+                    Doc::text(" do"),
+                ]);
+                let stmts = self.block(&for_expr.body, expr.span.end() - 3);
+                Doc::Concat(vec![head, soft_block(stmts), Doc::text("end")]).group()
+            }
+            ExprKind::Break => Doc::text("break"),
+            ExprKind::Continue => Doc::text("continue"),
+            ExprKind::Return(None) => Doc::text("return"),
+            ExprKind::Return(Some(value)) => Doc::text("return ").append(self.expr(value)),
+            ExprKind::Lambda(lambda) => {
+                let params: Vec<_> = lambda.params.iter().map(|p| self.param_group(p)).collect();
+                Doc::Concat(vec![
+                    Doc::join(params, Doc::text(" ")),
+                    Doc::text(" =>"),
+                    self.rhs(&lambda.body),
+                ])
+            }
+        }
+    }
+
+    fn if_expr(&mut self, if_expr: &If) -> Doc {
+        let mut doc = Vec::new();
+        for (i, branch) in if_expr.branches.iter().enumerate() {
+            // the body ends where the next branch starts
+            let end = match (if_expr.branches.get(i + 1), &if_expr.else_branch) {
+                (Some(next), _) => next.span.start(),
+                (None, Some(else_branch)) => else_branch.span.start(),
+                (None, None) => if_expr.span.end() - 3,
+            };
+            doc.extend([
+                Doc::text(if i == 0 { "if " } else { "elif " }),
+                self.expr(&branch.cond).nest(INDENT),
+                Doc::text(" then"),
+            ]);
+            let stmts = self.block(&branch.body, end);
+            doc.push(soft_block(stmts));
+        }
+        if let Some(else_branch) = &if_expr.else_branch {
+            let stmts = self.block(else_branch, if_expr.span.end() - 3);
+            doc.extend([Doc::text("else"), soft_block(stmts)]);
+        }
+        doc.push(Doc::text("end"));
+        Doc::Concat(doc).group()
+    }
+
+    fn match_expr(&mut self, match_expr: &Match) -> Doc {
+        let head = Doc::Concat(vec![
+            Doc::text("match "),
+            self.expr(&match_expr.scrutinee).nest(INDENT),
+            Doc::text(" with"),
+        ]);
+        let mut list = List::default();
+        for arm in &match_expr.arms {
+            self.element(&mut list, arm.span, |f| {
+                Doc::Concat(vec![
+                    Doc::text("| "),
+                    pattern(&arm.pat),
+                    Doc::text(" =>"),
+                    f.rhs(&arm.body).nest(INDENT),
+                ])
+            });
+        }
+        let arms = self.finish(list, match_expr.span.end() - 3);
+        Doc::Concat(vec![head, Self::arms(arms, Doc::Nil)])
+    }
+
+    fn when(&mut self, when: &When) -> Doc {
+        let end = when.span.end() - 3;
+        let mut list = List::default();
+        for arm in &when.arms {
+            self.element(&mut list, arm.span, |f| {
+                Doc::Concat(vec![
+                    Doc::text("| "),
+                    f.operand(&arm.cond, Prec::Comparison),
+                    Doc::text(" =>"),
+                    f.rhs(&arm.body).nest(INDENT),
+                ])
+            });
+        }
+        let arms_end = when.else_branch.as_ref().map_or(end, |b| b.span.start());
+        let arms = self.finish(list, arms_end);
+        let else_branch = match &when.else_branch {
+            Some(else_branch) => {
+                let stmts = self.block(else_branch, end);
+                Doc::Concat(vec![Doc::HardLine, Doc::text("else"), lines(stmts).group()])
+            }
+            None => Doc::Nil,
+        };
+        Doc::text("when").append(Self::arms(arms, else_branch))
+    }
+
+    /// The arms of a `match` or `when` on their own lines, followed by its
+    /// `else` branch and `end`.
+    fn arms(arms: Vec<Doc>, else_branch: Doc) -> Doc {
+        if arms.is_empty() && matches!(else_branch, Doc::Nil) {
+            return Doc::text(" end");
+        }
+        let lines = arms.into_iter().flat_map(|arm| [Doc::HardLine, arm]);
+        Doc::Concat(vec![
+            Doc::concat(lines),
+            else_branch,
+            Doc::HardLine,
+            Doc::text("end"),
+        ])
+    }
+}
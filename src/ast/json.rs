@@ -0,0 +1,481 @@
+//! Conversion of the AST to and from JSON, e.g. to feed external tools.
+//!
+//! # Schema
+//!
+//! - A node is an object tagged with its type in `"node"`, followed by its
+//!   fields under their names in the AST, e.g.
+//!   `{"node": "Path", "id": 0, "span": {...}, "segments": [...]}`.
+//! - A span is `{"start", "len", "line", "col", "end_line", "end_col"}`, with
+//!   byte offsets, and lines and columns starting at 1. Columns count
+//!   characters. Only `start` and `len` are read back.
+//! - Node ids are numbers. Identifiers and string literals hold their text.
+//! - An enum with data is an object tagged with its variant in `"variant"`,
+//!   e.g. `{"variant": "Ref", "mutable": true, "ty": {...}}`. The fields of
+//!   tuple variants are named as in the tables below.
+//! - An enum without data, like `BinOp`, is the name of its variant, e.g.
+//!   `"Add"`.
+//! - `Option`s are `null` or their value, `Vec`s are arrays and `P`s are
+//!   their node.
+//! - A token of an attribute's arguments is `{"kind", "text", "span"}`, with
+//!   the name of its `token::Kind`.
+//! - Literal values are numbers, except for `Char` and `Str` which are
+//!   strings. Infinite floats are written as `1e999`.
+//!
+//! | enum          | tuple variants                                              |
+//! |---------------|-------------------------------------------------------------|
+//! | `TokenTree`   | `Token(token)`, `Delimited(args)`                           |
+//! | `LitKind`     | `Char(value)`, `Str(value)`                                 |
+//! | `Visibility`  | `Public(span)`                                              |
+//! | `TyKind`      | `Path(path)`, `Tuple(tuple)`, `Array(array)`, `Fn(fn_ty)`,  |
+//! |               | `RawPtr(ty)`                                                |
+//! | `TyDefKind`   | `Alias(ty)`, `Record(def)`, `Enum(def)`                     |
+//! | `PatternKind` | `Binding(binding)`, `Lit(lit)`, `Neg(lit)`, `Tuple(pats)`,  |
+//! |               | `Array(pats)`, `Ctor(path, pats)`                           |
+//! | `StmtKind`    | `Let(decl)`, `Expr(expr)`                                   |
+//! | `ExprKind`    | `Lit(lit)`, `Path(path)`, `Tuple(exprs)`,                   |
+//! |               | `Call(callee, args)`, `Unary(op, expr)`,                    |
+//! |               | `Binary(op, lhs, rhs)`, `Assign(place, value)`,             |
+//! |               | `Is(expr, pat)`, `Block(block)`, `If(expr)`,                |
+//! |               | `Match(expr)`, `When(expr)`, `Loop(block)`, `For(expr)`,    |
+//! |               | `Return(expr)`, `Lambda(expr)`                              |
+//! | `ImportKind`  | `Single(rename)`                                            |
+//! | `ItemKind`    | `TyDef(def)`, `FnDef(def)`, `ConstDef(def)`, `Impl(def)`,   |
+//! |               | `Module(def)`, `Import(def)`                                |
+
+use super::*;
+use crate::{
+    interner,
+    json::{Error, Json, Result},
+    lexer::Scanner,
+    span::LineIndex,
+    token::Kind,
+};
+
+pub trait ToJson {
+    fn to_json(&self, lines: &LineIndex) -> Json;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(json: &Json) -> Result<Self>;
+}
+
+/// Converts a node to JSON. `src` is the source the node was parsed from,
+/// which gives the lines and columns of spans.
+pub fn to_json<T: ToJson>(node: &T, src: &str) -> Json {
+    node.to_json(&LineIndex::new(src))
+}
+
+/// Rebuilds a node from the JSON given by `to_json`.
+pub fn from_json<T: FromJson>(json: &Json) -> Result<T> {
+    T::from_json(json)
+}
+
+fn field<T: FromJson>(json: &Json, key: &str) -> Result<T> {
+    T::from_json(json.field(key)?)
+}
+
+fn object(fields: Vec<(&str, Json)>) -> Json {
+    Json::Object(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self, lines: &LineIndex) -> Json {
+        (**self).to_json(lines)
+    }
+}
+
+impl<T: ToJson> ToJson for P<T> {
+    fn to_json(&self, lines: &LineIndex) -> Json {
+        (**self).to_json(lines)
+    }
+}
+
+impl<T: FromJson> FromJson for P<T> {
+    fn from_json(json: &Json) -> Result<Self> {
+        T::from_json(json).map(P::new)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self, lines: &LineIndex) -> Json {
+        match self {
+            Some(value) => value.to_json(lines),
+            None => Json::Null,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(json: &Json) -> Result<Self> {
+        match json {
+            Json::Null => Ok(None),
+            json => T::from_json(json).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self, lines: &LineIndex) -> Json {
+        Json::Array(self.iter().map(|value| value.to_json(lines)).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(json: &Json) -> Result<Self> {
+        json.as_array()?.iter().map(T::from_json).collect()
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self, _: &LineIndex) -> Json {
+        Json::Bool(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(json: &Json) -> Result<Self> {
+        json.as_bool()
+    }
+}
+
+impl ToJson for u128 {
+    fn to_json(&self, _: &LineIndex) -> Json {
+        Json::number(self)
+    }
+}
+
+impl FromJson for u128 {
+    fn from_json(json: &Json) -> Result<Self> {
+        json.as_u128()
+    }
+}
+
+impl ToJson for f64 {
+    fn to_json(&self, _: &LineIndex) -> Json {
+        Json::float(*self)
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(json: &Json) -> Result<Self> {
+        json.as_f64()
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self, _: &LineIndex) -> Json {
+        Json::String(self.to_string())
+    }
+}
+
+impl FromJson for char {
+    fn from_json(json: &Json) -> Result<Self> {
+        let mut chars = json.as_str()?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(Error::InvalidValue("a single character")),
+        }
+    }
+}
+
+impl ToJson for Symbol {
+    fn to_json(&self, _: &LineIndex) -> Json {
+        Json::String(interner::get(*self).to_string())
+    }
+}
+
+impl FromJson for Symbol {
+    fn from_json(json: &Json) -> Result<Self> {
+        json.as_str().map(interner::intern)
+    }
+}
+
+impl ToJson for NodeId {
+    fn to_json(&self, _: &LineIndex) -> Json {
+        Json::number(self.index())
+    }
+}
+
+impl FromJson for NodeId {
+    fn from_json(json: &Json) -> Result<Self> {
+        json.as_u32().map(NodeId::new)
+    }
+}
+
+impl ToJson for Span {
+    fn to_json(&self, lines: &LineIndex) -> Json {
+        let (line, col) = lines.line_col(self.start());
+        let (end_line, end_col) = lines.line_col(self.start() + self.len());
+        object(vec![
+            ("start", Json::number(self.start())),
+            ("len", Json::number(self.len())),
+            ("line", Json::number(line)),
+            ("col", Json::number(col)),
+            ("end_line", Json::number(end_line)),
+            ("end_col", Json::number(end_col)),
+        ])
+    }
+}
+
+impl FromJson for Span {
+    fn from_json(json: &Json) -> Result<Self> {
+        Ok(Span::new(
+            json.field("start")?.as_u32()?,
+            json.field("len")?.as_u32()?,
+        ))
+    }
+}
+
+impl ToJson for Token {
+    fn to_json(&self, lines: &LineIndex) -> Json {
+        object(vec![
+            ("kind", Json::String(format!("{:?}", self.kind()))),
+            ("text", Json::String(self.repr().to_string())),
+            ("span", self.span().to_json(lines)),
+        ])
+    }
+}
+
+/// Literals and identifiers keep their text. Other tokens are found again by
+/// scanning their text.
+impl FromJson for Token {
+    fn from_json(json: &Json) -> Result<Self> {
+        let kind = json.field("kind")?.as_str()?;
+        let text = json.field("text")?.as_str()?;
+        let span: Span = field(json, "span")?;
+
+        let literal = match kind {
+            "IntLit" => Some(Kind::IntLit),
+            "FloatLit" => Some(Kind::FloatLit),
+            "ComplexLit" => Some(Kind::ComplexLit),
+            "CharLit" => Some(Kind::CharLit),
+            "StrLit" => Some(Kind::StrLit),
+            "Ident" => Some(Kind::Ident),
+            _ => None,
+        };
+        if let Some(kind) = literal {
+            return Ok(Token::new(kind, span, Some(interner::intern(text))));
+        }
+
+        // quotes would start a literal
+        let invalid = || Error::InvalidToken(format!("{kind} `{text}`"));
+        if text.starts_with(['"', '\'']) {
+            return Err(invalid());
+        }
+        match Scanner::new(text).scan() {
+            Some(token)
+                if token.kind().repr() == Some(text) && format!("{:?}", token.kind()) == kind =>
+            {
+                Ok(Token::new(token.kind(), span, None))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Structs become objects tagged with their name, holding every field.
+macro_rules! json_struct {
+    ($($ty:ident { $($field:ident),* })*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self, lines: &LineIndex) -> Json {
+                    object(vec![
+                        ("node", Json::String(stringify!($ty).to_string())),
+                        $((stringify!($field), self.$field.to_json(lines)),)*
+                    ])
+                }
+            }
+
+            impl FromJson for $ty {
+                fn from_json(json: &Json) -> Result<Self> {
+                    let node = json.field("node")?.as_str()?;
+                    if node != stringify!($ty) {
+                        return Err(Error::UnexpectedNode(node.to_string()));
+                    }
+                    Ok($ty {
+                        $($field: field(json, stringify!($field))?,)*
+                    })
+                }
+            }
+        )*
+    };
+}
+
+/// Enums become objects tagged with their variant, holding its fields.
+/// Tuple variants name their fields.
+macro_rules! json_enum {
+    ($($ty:ident {
+        $($variant:ident $(( $($field:ident),* ))? $({ $($named:ident),* })?),* $(,)?
+    })*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self, lines: &LineIndex) -> Json {
+                    match self {
+                        $(
+                            $ty::$variant $(( $($field),* ))? $({ $($named),* })? => object(vec![
+                                ("variant", Json::String(stringify!($variant).to_string())),
+                                $($((stringify!($field), $field.to_json(lines)),)*)?
+                                $($((stringify!($named), $named.to_json(lines)),)*)?
+                            ]),
+                        )*
+                    }
+                }
+            }
+
+            impl FromJson for $ty {
+                fn from_json(json: &Json) -> Result<Self> {
+                    match json.field("variant")?.as_str()? {
+                        $(
+                            stringify!($variant) => Ok($ty::$variant
+                                $(( $(field(json, stringify!($field))?),* ))?
+                                $({ $($named: field(json, stringify!($named))?),* })?),
+                        )*
+                        variant => Err(Error::UnknownVariant(variant.to_string())),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+/// Enums without data become the name of their variant.
+macro_rules! json_names {
+    ($($ty:ident { $($variant:ident),* $(,)? })*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self, _: &LineIndex) -> Json {
+                    let name = match self {
+                        $($ty::$variant => stringify!($variant),)*
+                    };
+                    Json::String(name.to_string())
+                }
+            }
+
+            impl FromJson for $ty {
+                fn from_json(json: &Json) -> Result<Self> {
+                    match json.as_str()? {
+                        $(stringify!($variant) => Ok($ty::$variant),)*
+                        variant => Err(Error::UnknownVariant(variant.to_string())),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+json_names! {
+    Delimiter { Paren, Bracket }
+    AttrStyle { Outer, Inner }
+    IntTy { U8, U16, U32, U64, I8, I16, I32, I64 }
+    FloatTy { F32, F64 }
+    UnOp { Neg, Deref, Ref, RefMut, Raw }
+    BinOp { Add, Sub, Mul, Div, Eq, Neq, Lt, Gt, LtEq, GtEq }
+    ReceiverKind { Value, MutValue, Ref, RefMut }
+}
+
+json_enum! {
+    TokenTree { Token(token), Delimited(args) }
+    LitKind {
+        Int { value, suffix },
+        Float { value, suffix },
+        Complex { re, im },
+        Char(value),
+        Str(value),
+    }
+    Visibility { Private, Public(span) }
+    TyKind {
+        Path(path),
+        Tuple(tuple),
+        Array(array),
+        Fn(fn_ty),
+        RawPtr(ty),
+        Ref { mutable, ty },
+        Never,
+    }
+    TyDefKind { Alias(ty), Record(def), Enum(def) }
+    PatternKind {
+        Wild,
+        Binding(binding),
+        Lit(lit),
+        Neg(lit),
+        Tuple(pats),
+        Array(pats),
+        Ctor(path, pats),
+    }
+    StmtKind { Let(decl), Expr(expr) }
+    ExprKind {
+        Lit(lit),
+        Path(path),
+        Tuple(exprs),
+        Call(callee, args),
+        Unary(op, expr),
+        Binary(op, lhs, rhs),
+        Assign(place, value),
+        Is(expr, pat),
+        Block(block),
+        If(expr),
+        Match(expr),
+        When(expr),
+        Loop(block),
+        For(expr),
+        Break,
+        Continue,
+        Return(expr),
+        Lambda(expr),
+    }
+    ImportKind { Single(rename), Glob }
+    ItemKind {
+        TyDef(def),
+        FnDef(def),
+        ConstDef(def),
+        Impl(def),
+        Module(def),
+        Import(def),
+    }
+}
+
+json_struct! {
+    DelimArgs { span, delim, tokens }
+    Attribute { id, span, style, path, args }
+    Lit { span, kind }
+    Ident { span, name }
+    Path { id, span, segments }
+    Tuple { id, span, types }
+    Array { id, span, ty, len }
+    FnTy { id, span, param, ret }
+    Ty { id, span, kind }
+    Variant { id, span, attrs, name, data }
+    Enum { id, span, variants }
+    Param { id, span, name }
+    ParamGroup { id, span, attrs, vis, params, ty }
+    Record { id, span, fields }
+    TyDef { id, span, vis, name, ty }
+    Binding { id, span, mutable, name }
+    Pattern { id, span, kind }
+    Let { id, span, pat, ty, init }
+    Stmt { id, span, kind }
+    Block { id, span, stmts, expr }
+    IfBranch { id, span, cond, body }
+    If { id, span, branches, else_branch }
+    MatchArm { id, span, pat, body }
+    Match { id, span, scrutinee, arms }
+    WhenArm { id, span, cond, body }
+    When { id, span, arms, else_branch }
+    For { id, span, pat, iter, body }
+    Lambda { id, span, params, body }
+    Expr { id, span, kind }
+    Receiver { id, span, kind }
+    FnDef { id, span, vis, name, receiver, params, ret, body }
+    ConstDef { id, span, vis, name, ty, value }
+    Method { id, span, attrs, fn_def }
+    Impl { id, span, target, methods }
+    Module { id, span, name, attrs, items }
+    Import { id, span, path, kind }
+    Item { id, span, attrs, kind }
+    SourceFile { id, span, attrs, items }
+}
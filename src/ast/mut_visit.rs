@@ -0,0 +1,545 @@
+use super::*;
+
+/// Traverses the AST by mutable reference, for passes rewriting it in place.
+/// Like [`Visitor`](super::visit::Visitor), every method defaults to the
+/// matching `walk_*` function.
+pub trait MutVisitor: Sized {
+    fn visit_source_file(&mut self, file: &mut SourceFile) {
+        walk_source_file(self, file)
+    }
+
+    fn visit_item(&mut self, item: &mut Item) {
+        walk_item(self, item)
+    }
+
+    fn visit_attribute(&mut self, attr: &mut Attribute) {
+        walk_attribute(self, attr)
+    }
+
+    fn visit_delim_args(&mut self, args: &mut DelimArgs) {
+        walk_delim_args(self, args)
+    }
+
+    fn visit_visibility(&mut self, vis: &mut Visibility) {
+        walk_visibility(self, vis)
+    }
+
+    fn visit_ident(&mut self, ident: &mut Ident) {
+        walk_ident(self, ident)
+    }
+
+    fn visit_path(&mut self, path: &mut Path) {
+        walk_path(self, path)
+    }
+
+    fn visit_lit(&mut self, lit: &mut Lit) {
+        walk_lit(self, lit)
+    }
+
+    fn visit_ty_def(&mut self, ty_def: &mut TyDef) {
+        walk_ty_def(self, ty_def)
+    }
+
+    fn visit_ty_def_kind(&mut self, kind: &mut TyDefKind) {
+        walk_ty_def_kind(self, kind)
+    }
+
+    fn visit_record(&mut self, record: &mut Record) {
+        walk_record(self, record)
+    }
+
+    fn visit_enum(&mut self, enum_: &mut Enum) {
+        walk_enum(self, enum_)
+    }
+
+    fn visit_variant(&mut self, variant: &mut Variant) {
+        walk_variant(self, variant)
+    }
+
+    fn visit_param_group(&mut self, group: &mut ParamGroup) {
+        walk_param_group(self, group)
+    }
+
+    fn visit_param(&mut self, param: &mut Param) {
+        walk_param(self, param)
+    }
+
+    fn visit_ty(&mut self, ty: &mut Ty) {
+        walk_ty(self, ty)
+    }
+
+    fn visit_tuple(&mut self, tuple: &mut Tuple) {
+        walk_tuple(self, tuple)
+    }
+
+    fn visit_array(&mut self, array: &mut Array) {
+        walk_array(self, array)
+    }
+
+    fn visit_fn_ty(&mut self, fn_ty: &mut FnTy) {
+        walk_fn_ty(self, fn_ty)
+    }
+
+    fn visit_pattern(&mut self, pat: &mut Pattern) {
+        walk_pattern(self, pat)
+    }
+
+    fn visit_binding(&mut self, binding: &mut Binding) {
+        walk_binding(self, binding)
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_let(&mut self, let_: &mut Let) {
+        walk_let(self, let_)
+    }
+
+    fn visit_if(&mut self, if_: &mut If) {
+        walk_if(self, if_)
+    }
+
+    fn visit_if_branch(&mut self, branch: &mut IfBranch) {
+        walk_if_branch(self, branch)
+    }
+
+    fn visit_match(&mut self, match_: &mut Match) {
+        walk_match(self, match_)
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_when(&mut self, when: &mut When) {
+        walk_when(self, when)
+    }
+
+    fn visit_when_arm(&mut self, arm: &mut WhenArm) {
+        walk_when_arm(self, arm)
+    }
+
+    fn visit_for(&mut self, for_: &mut For) {
+        walk_for(self, for_)
+    }
+
+    fn visit_lambda(&mut self, lambda: &mut Lambda) {
+        walk_lambda(self, lambda)
+    }
+
+    fn visit_fn_def(&mut self, fn_def: &mut FnDef) {
+        walk_fn_def(self, fn_def)
+    }
+
+    fn visit_receiver(&mut self, receiver: &mut Receiver) {
+        walk_receiver(self, receiver)
+    }
+
+    fn visit_const_def(&mut self, const_def: &mut ConstDef) {
+        walk_const_def(self, const_def)
+    }
+
+    fn visit_impl(&mut self, impl_: &mut Impl) {
+        walk_impl(self, impl_)
+    }
+
+    fn visit_method(&mut self, method: &mut Method) {
+        walk_method(self, method)
+    }
+
+    fn visit_module(&mut self, module: &mut Module) {
+        walk_module(self, module)
+    }
+
+    fn visit_import(&mut self, import: &mut Import) {
+        walk_import(self, import)
+    }
+
+    /// Called on the span of every node, e.g. to move them after an edit.
+    fn visit_span(&mut self, _span: &mut Span) {}
+}
+
+pub fn walk_source_file<V: MutVisitor>(v: &mut V, file: &mut SourceFile) {
+    v.visit_span(&mut file.span);
+    for attr in &mut file.attrs {
+        v.visit_attribute(attr);
+    }
+    for item in &mut file.items {
+        v.visit_item(item);
+    }
+}
+
+pub fn walk_item<V: MutVisitor>(v: &mut V, item: &mut Item) {
+    v.visit_span(&mut item.span);
+    for attr in &mut item.attrs {
+        v.visit_attribute(attr);
+    }
+    match &mut item.kind {
+        ItemKind::TyDef(ty_def) => v.visit_ty_def(ty_def),
+        ItemKind::FnDef(fn_def) => v.visit_fn_def(fn_def),
+        ItemKind::ConstDef(const_def) => v.visit_const_def(const_def),
+        ItemKind::Impl(impl_) => v.visit_impl(impl_),
+        ItemKind::Module(module) => v.visit_module(module),
+        ItemKind::Import(import) => v.visit_import(import),
+    }
+}
+
+pub fn walk_delim_args<V: MutVisitor>(v: &mut V, args: &mut DelimArgs) {
+    v.visit_span(&mut args.span);
+    for tree in &mut args.tokens {
+        match tree {
+            TokenTree::Token(token) => {
+                let mut span = token.span();
+                v.visit_span(&mut span);
+                *token = Token::new(token.kind(), span, token.symbol());
+            }
+            TokenTree::Delimited(args) => v.visit_delim_args(args),
+        }
+    }
+}
+
+pub fn walk_visibility<V: MutVisitor>(v: &mut V, vis: &mut Visibility) {
+    if let Visibility::Public(span) = vis {
+        v.visit_span(span);
+    }
+}
+
+pub fn walk_ident<V: MutVisitor>(v: &mut V, ident: &mut Ident) {
+    v.visit_span(&mut ident.span);
+}
+
+pub fn walk_lit<V: MutVisitor>(v: &mut V, lit: &mut Lit) {
+    v.visit_span(&mut lit.span);
+}
+
+pub fn walk_receiver<V: MutVisitor>(v: &mut V, receiver: &mut Receiver) {
+    v.visit_span(&mut receiver.span);
+}
+
+pub fn walk_attribute<V: MutVisitor>(v: &mut V, attr: &mut Attribute) {
+    v.visit_span(&mut attr.span);
+    v.visit_path(&mut attr.path);
+    if let Some(args) = &mut attr.args {
+        v.visit_delim_args(args);
+    }
+}
+
+pub fn walk_path<V: MutVisitor>(v: &mut V, path: &mut Path) {
+    v.visit_span(&mut path.span);
+    for segment in &mut path.segments {
+        v.visit_ident(segment);
+    }
+}
+
+pub fn walk_ty_def<V: MutVisitor>(v: &mut V, ty_def: &mut TyDef) {
+    v.visit_span(&mut ty_def.span);
+    v.visit_visibility(&mut ty_def.vis);
+    v.visit_ident(&mut ty_def.name);
+    v.visit_ty_def_kind(&mut ty_def.ty);
+}
+
+pub fn walk_ty_def_kind<V: MutVisitor>(v: &mut V, kind: &mut TyDefKind) {
+    match kind {
+        TyDefKind::Alias(ty) => v.visit_ty(ty),
+        TyDefKind::Record(record) => v.visit_record(record),
+        TyDefKind::Enum(enum_) => v.visit_enum(enum_),
+    }
+}
+
+pub fn walk_record<V: MutVisitor>(v: &mut V, record: &mut Record) {
+    v.visit_span(&mut record.span);
+    for group in &mut record.fields {
+        v.visit_param_group(group);
+    }
+}
+
+pub fn walk_enum<V: MutVisitor>(v: &mut V, enum_: &mut Enum) {
+    v.visit_span(&mut enum_.span);
+    for variant in &mut enum_.variants {
+        v.visit_variant(variant);
+    }
+}
+
+pub fn walk_variant<V: MutVisitor>(v: &mut V, variant: &mut Variant) {
+    v.visit_span(&mut variant.span);
+    for attr in &mut variant.attrs {
+        v.visit_attribute(attr);
+    }
+    v.visit_ident(&mut variant.name);
+    for ty in &mut variant.data {
+        v.visit_ty(ty);
+    }
+}
+
+pub fn walk_param_group<V: MutVisitor>(v: &mut V, group: &mut ParamGroup) {
+    v.visit_span(&mut group.span);
+    for attr in &mut group.attrs {
+        v.visit_attribute(attr);
+    }
+    v.visit_visibility(&mut group.vis);
+    for param in &mut group.params {
+        v.visit_param(param);
+    }
+    v.visit_ty(&mut group.ty);
+}
+
+pub fn walk_param<V: MutVisitor>(v: &mut V, param: &mut Param) {
+    v.visit_span(&mut param.span);
+    v.visit_ident(&mut param.name);
+}
+
+pub fn walk_ty<V: MutVisitor>(v: &mut V, ty: &mut Ty) {
+    v.visit_span(&mut ty.span);
+    match &mut ty.kind {
+        TyKind::Path(path) => v.visit_path(path),
+        TyKind::Tuple(tuple) => v.visit_tuple(tuple),
+        TyKind::Array(array) => v.visit_array(array),
+        TyKind::Fn(fn_ty) => v.visit_fn_ty(fn_ty),
+        TyKind::RawPtr(ty) | TyKind::Ref { ty, .. } => v.visit_ty(ty),
+        TyKind::Never => {}
+    }
+}
+
+pub fn walk_tuple<V: MutVisitor>(v: &mut V, tuple: &mut Tuple) {
+    v.visit_span(&mut tuple.span);
+    for ty in &mut tuple.types {
+        v.visit_ty(ty);
+    }
+}
+
+pub fn walk_array<V: MutVisitor>(v: &mut V, array: &mut Array) {
+    v.visit_span(&mut array.span);
+    v.visit_ty(&mut array.ty);
+    v.visit_expr(&mut array.len);
+}
+
+pub fn walk_fn_ty<V: MutVisitor>(v: &mut V, fn_ty: &mut FnTy) {
+    v.visit_span(&mut fn_ty.span);
+    v.visit_ty(&mut fn_ty.param);
+    v.visit_ty(&mut fn_ty.ret);
+}
+
+pub fn walk_pattern<V: MutVisitor>(v: &mut V, pat: &mut Pattern) {
+    v.visit_span(&mut pat.span);
+    match &mut pat.kind {
+        PatternKind::Wild => {}
+        PatternKind::Binding(binding) => v.visit_binding(binding),
+        PatternKind::Lit(lit) | PatternKind::Neg(lit) => v.visit_lit(lit),
+        PatternKind::Tuple(pats) | PatternKind::Array(pats) => {
+            for pat in pats {
+                v.visit_pattern(pat);
+            }
+        }
+        PatternKind::Ctor(path, args) => {
+            v.visit_path(path);
+            for arg in args {
+                v.visit_pattern(arg);
+            }
+        }
+    }
+}
+
+pub fn walk_binding<V: MutVisitor>(v: &mut V, binding: &mut Binding) {
+    v.visit_span(&mut binding.span);
+    v.visit_ident(&mut binding.name);
+}
+
+pub fn walk_expr<V: MutVisitor>(v: &mut V, expr: &mut Expr) {
+    v.visit_span(&mut expr.span);
+    match &mut expr.kind {
+        ExprKind::Lit(lit) => v.visit_lit(lit),
+        ExprKind::Path(path) => v.visit_path(path),
+        ExprKind::Tuple(exprs) => {
+            for expr in exprs {
+                v.visit_expr(expr);
+            }
+        }
+        ExprKind::Call(callee, args) => {
+            v.visit_expr(callee);
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Unary(_, operand) => v.visit_expr(operand),
+        ExprKind::Binary(_, lhs, rhs) | ExprKind::Assign(lhs, rhs) => {
+            v.visit_expr(lhs);
+            v.visit_expr(rhs);
+        }
+        ExprKind::Is(expr, pat) => {
+            v.visit_expr(expr);
+            v.visit_pattern(pat);
+        }
+        ExprKind::Block(block) | ExprKind::Loop(block) => v.visit_block(block),
+        ExprKind::If(if_) => v.visit_if(if_),
+        ExprKind::Match(match_) => v.visit_match(match_),
+        ExprKind::When(when) => v.visit_when(when),
+        ExprKind::For(for_) => v.visit_for(for_),
+        ExprKind::Break | ExprKind::Continue => {}
+        ExprKind::Return(value) => {
+            if let Some(value) = value {
+                v.visit_expr(value);
+            }
+        }
+        ExprKind::Lambda(lambda) => v.visit_lambda(lambda),
+    }
+}
+
+pub fn walk_block<V: MutVisitor>(v: &mut V, block: &mut Block) {
+    v.visit_span(&mut block.span);
+    for stmt in &mut block.stmts {
+        v.visit_stmt(stmt);
+    }
+    if let Some(expr) = &mut block.expr {
+        v.visit_expr(expr);
+    }
+}
+
+pub fn walk_stmt<V: MutVisitor>(v: &mut V, stmt: &mut Stmt) {
+    v.visit_span(&mut stmt.span);
+    match &mut stmt.kind {
+        StmtKind::Let(let_) => v.visit_let(let_),
+        StmtKind::Expr(expr) => v.visit_expr(expr),
+    }
+}
+
+pub fn walk_let<V: MutVisitor>(v: &mut V, let_: &mut Let) {
+    v.visit_span(&mut let_.span);
+    v.visit_pattern(&mut let_.pat);
+    if let Some(ty) = &mut let_.ty {
+        v.visit_ty(ty);
+    }
+    v.visit_expr(&mut let_.init);
+}
+
+pub fn walk_if<V: MutVisitor>(v: &mut V, if_: &mut If) {
+    v.visit_span(&mut if_.span);
+    for branch in &mut if_.branches {
+        v.visit_if_branch(branch);
+    }
+    if let Some(block) = &mut if_.else_branch {
+        v.visit_block(block);
+    }
+}
+
+pub fn walk_if_branch<V: MutVisitor>(v: &mut V, branch: &mut IfBranch) {
+    v.visit_span(&mut branch.span);
+    v.visit_expr(&mut branch.cond);
+    v.visit_block(&mut branch.body);
+}
+
+pub fn walk_match<V: MutVisitor>(v: &mut V, match_: &mut Match) {
+    v.visit_span(&mut match_.span);
+    v.visit_expr(&mut match_.scrutinee);
+    for arm in &mut match_.arms {
+        v.visit_match_arm(arm);
+    }
+}
+
+pub fn walk_match_arm<V: MutVisitor>(v: &mut V, arm: &mut MatchArm) {
+    v.visit_span(&mut arm.span);
+    v.visit_pattern(&mut arm.pat);
+    v.visit_expr(&mut arm.body);
+}
+
+pub fn walk_when<V: MutVisitor>(v: &mut V, when: &mut When) {
+    v.visit_span(&mut when.span);
+    for arm in &mut when.arms {
+        v.visit_when_arm(arm);
+    }
+    if let Some(block) = &mut when.else_branch {
+        v.visit_block(block);
+    }
+}
+
+pub fn walk_when_arm<V: MutVisitor>(v: &mut V, arm: &mut WhenArm) {
+    v.visit_span(&mut arm.span);
+    v.visit_expr(&mut arm.cond);
+    v.visit_expr(&mut arm.body);
+}
+
+pub fn walk_for<V: MutVisitor>(v: &mut V, for_: &mut For) {
+    v.visit_span(&mut for_.span);
+    v.visit_pattern(&mut for_.pat);
+    v.visit_expr(&mut for_.iter);
+    v.visit_block(&mut for_.body);
+}
+
+pub fn walk_lambda<V: MutVisitor>(v: &mut V, lambda: &mut Lambda) {
+    v.visit_span(&mut lambda.span);
+    for group in &mut lambda.params {
+        v.visit_param_group(group);
+    }
+    v.visit_expr(&mut lambda.body);
+}
+
+pub fn walk_fn_def<V: MutVisitor>(v: &mut V, fn_def: &mut FnDef) {
+    v.visit_span(&mut fn_def.span);
+    v.visit_visibility(&mut fn_def.vis);
+    v.visit_ident(&mut fn_def.name);
+    if let Some(receiver) = &mut fn_def.receiver {
+        v.visit_receiver(receiver);
+    }
+    for group in &mut fn_def.params {
+        v.visit_param_group(group);
+    }
+    if let Some(ret) = &mut fn_def.ret {
+        v.visit_ty(ret);
+    }
+    v.visit_expr(&mut fn_def.body);
+}
+
+pub fn walk_const_def<V: MutVisitor>(v: &mut V, const_def: &mut ConstDef) {
+    v.visit_span(&mut const_def.span);
+    v.visit_visibility(&mut const_def.vis);
+    v.visit_ident(&mut const_def.name);
+    if let Some(ty) = &mut const_def.ty {
+        v.visit_ty(ty);
+    }
+    v.visit_expr(&mut const_def.value);
+}
+
+pub fn walk_impl<V: MutVisitor>(v: &mut V, impl_: &mut Impl) {
+    v.visit_span(&mut impl_.span);
+    v.visit_path(&mut impl_.target);
+    for method in &mut impl_.methods {
+        v.visit_method(method);
+    }
+}
+
+pub fn walk_method<V: MutVisitor>(v: &mut V, method: &mut Method) {
+    v.visit_span(&mut method.span);
+    for attr in &mut method.attrs {
+        v.visit_attribute(attr);
+    }
+    v.visit_fn_def(&mut method.fn_def);
+}
+
+pub fn walk_module<V: MutVisitor>(v: &mut V, module: &mut Module) {
+    v.visit_span(&mut module.span);
+    v.visit_ident(&mut module.name);
+    for attr in &mut module.attrs {
+        v.visit_attribute(attr);
+    }
+    if let Some(items) = &mut module.items {
+        for item in items {
+            v.visit_item(item);
+        }
+    }
+}
+
+pub fn walk_import<V: MutVisitor>(v: &mut V, import: &mut Import) {
+    v.visit_span(&mut import.span);
+    v.visit_path(&mut import.path);
+    if let ImportKind::Single(Some(alias)) = &mut import.kind {
+        v.visit_ident(alias);
+    }
+}
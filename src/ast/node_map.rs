@@ -0,0 +1,69 @@
+use super::NodeId;
+
+/// A side table storing a value for some nodes of the AST, e.g. what a path
+/// resolves to. Ids are dense, so the table is a vector indexed by them.
+#[derive(Clone, Debug)]
+pub struct NodeMap<T> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+
+    /// Stores `value` for `id`, returning the value it replaces.
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        if id.index() >= self.values.len() {
+            self.values.resize_with(id.index() + 1, || None);
+        }
+        self.values[id.index()].replace(value)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.values.get(id.index())?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.values.get_mut(id.index())?.as_mut()
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        self.values.get_mut(id.index())?.take()
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.iter().filter(|v| v.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(Option::is_none)
+    }
+
+    /// The stored values, by increasing id.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.values.iter().enumerate().filter_map(|(i, v)| {
+            let id = NodeId::new(u32::try_from(i).unwrap());
+            v.as_ref().map(|v| (id, v))
+        })
+    }
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::ops::Index<NodeId> for NodeMap<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId) -> &T {
+        self.get(id)
+            .unwrap_or_else(|| panic!("no value for node {id:?}"))
+    }
+}
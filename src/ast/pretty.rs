@@ -1,112 +1,740 @@
-use crate::interner;
+//! S-expressions of the AST, e.g. `(call (path f) (lit 1))`.
+//!
+//! The `Display` impls print them on a single line. A [`Printer`] breaks the
+//! lists that don't fit in its width, with their elements on lines of their
+//! own, and can annotate each node with its span.
+
+use crate::{doc::Doc, interner, span::LineIndex, token::Kind};
 
 use super::*;
 
-use std::fmt::{self, Display, Formatter};
+use std::fmt::{self, Display, Formatter, Write};
 
-impl Display for Ident {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.write_str(interner::get(self.name))
+/// Indentation of the elements of a broken list.
+const INDENT: usize = 2;
+
+/// Lays s-expressions out to a width.
+///
+/// ```text
+/// (fun f
+///   (param x (path f64))
+///   (block (stmt (call (path print) (path x)))))
+/// ```
+pub struct Printer<'src> {
+    width: usize,
+    lines: Option<LineIndex<'src>>,
+}
+
+impl<'src> Printer<'src> {
+    pub fn new(width: usize) -> Self {
+        Self { width, lines: None }
+    }
+
+    /// Annotates nodes with where they lie in `src`, the source they were
+    /// parsed from, as `@line:col-line:col` after the head of their list,
+    /// e.g. `(path@1:1-1:2 f)`, or after them if they print as an atom, like
+    /// params. The annotations can't be read back.
+    pub fn with_spans(mut self, src: &'src str) -> Self {
+        self.lines = Some(LineIndex::new(src));
+        self
+    }
+
+    pub fn print<T: Pretty + ?Sized>(&self, node: &T) -> String {
+        node.pretty(self.lines.as_ref()).doc().render(self.width)
     }
 }
 
-impl Display for Path {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "(path {}", self.segments[0])?;
-        for segment in self.segments.iter().skip(1) {
-            write!(f, " {}", segment)?;
+/// The line index to annotate spans with, if any.
+pub type Lines<'a> = Option<&'a LineIndex<'a>>;
+
+pub trait Pretty {
+    fn pretty(&self, lines: Lines) -> Tree;
+}
+
+/// An s-expression, built from a node to be printed.
+#[derive(Clone, Debug)]
+pub struct Tree {
+    kind: TreeKind,
+    /// The annotated span, after the head of a list or after an atom.
+    span: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+enum TreeKind {
+    Atom(String),
+    List(&'static str, Vec<Tree>),
+}
+
+impl Tree {
+    fn atom(text: impl Display) -> Self {
+        Self {
+            kind: TreeKind::Atom(text.to_string()),
+            span: None,
         }
-        write!(f, ")")
     }
-}
 
-impl Display for Tuple {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "(tup {}", self.types[0])?;
-        for ty in self.types.iter().skip(1) {
-            write!(f, " {ty}")?;
+    fn list(head: &'static str) -> Self {
+        Self {
+            kind: TreeKind::List(head, Vec::new()),
+            span: None,
         }
-        write!(f, ")")
+    }
+
+    fn with(mut self, item: Tree) -> Self {
+        if let TreeKind::List(_, items) = &mut self.kind {
+            items.push(item);
+        }
+        self
+    }
+
+    fn with_all(self, items: impl IntoIterator<Item = Tree>) -> Self {
+        items.into_iter().fold(self, Tree::with)
+    }
+
+    /// Annotates the tree with `span`, unless the node it was printed from
+    /// already did, like a path inside the expression holding it.
+    fn at(mut self, span: Span, lines: Lines) -> Self {
+        if let Some(lines) = lines {
+            self.span.get_or_insert_with(|| {
+                let (line, col) = lines.line_col(span.start());
+                let (end_line, end_col) = lines.line_col(span.end());
+                format!("@{line}:{col}-{end_line}:{end_col}")
+            });
+        }
+        self
+    }
+
+    /// The atom, or the opening of the list up to its first element.
+    fn first(&self) -> String {
+        let span = self.span.as_deref().unwrap_or("");
+        match &self.kind {
+            TreeKind::Atom(text) => format!("{text}{span}"),
+            TreeKind::List(head, _) => format!("({head}{span}"),
+        }
+    }
+
+    fn write_flat(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.first())?;
+        if let TreeKind::List(_, items) = &self.kind {
+            for item in items {
+                f.write_char(' ')?;
+                item.write_flat(f)?;
+            }
+            f.write_char(')')?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the atoms leading a list on the line of its head, e.g.
+    /// `(bind mut x)`, and otherwise lets each element go on its own line.
+    fn doc(&self) -> Doc {
+        let TreeKind::List(_, items) = &self.kind else {
+            return Doc::text(self.first());
+        };
+        let leading = items
+            .iter()
+            .take_while(|item| matches!(item.kind, TreeKind::Atom(_)))
+            .count();
+        let mut first = self.first();
+        for item in &items[..leading] {
+            first.push(' ');
+            first.push_str(&item.first());
+        }
+        let rest = items[leading..]
+            .iter()
+            .map(|item| Doc::Line.append(item.doc()));
+        Doc::text(first)
+            .append(Doc::concat(rest).nest(INDENT))
+            .append(Doc::text(")"))
+            .group()
     }
 }
 
-impl Display for Array {
+impl Display for Tree {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "(array {} {})",
-            self.ty,
-            interner::get(self.len.symbol().unwrap())
-        )
+        self.write_flat(f)
     }
 }
 
-impl Display for TyKind {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+impl<T: Pretty + ?Sized> Pretty for &T {
+    fn pretty(&self, lines: Lines) -> Tree {
+        (**self).pretty(lines)
+    }
+}
+
+impl<T: Pretty> Pretty for P<T> {
+    fn pretty(&self, lines: Lines) -> Tree {
+        (**self).pretty(lines)
+    }
+}
+
+fn all<'a, T: Pretty + 'a>(nodes: impl IntoIterator<Item = &'a T>, lines: Lines) -> Vec<Tree> {
+    nodes.into_iter().map(|node| node.pretty(lines)).collect()
+}
+
+/// Displays the nodes through their s-expressions.
+macro_rules! display {
+    ($($ty:ty),* $(,)?) => {$(
+        impl Display for $ty {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                self.pretty(None).write_flat(f)
+            }
+        }
+    )*};
+}
+
+display! {
+    DelimArgs, TokenTree, Attribute, Lit, Path, Tuple, Array, FnTy, TyKind, Ty,
+    Variant, Enum, Param, ParamGroup, Record, TyDefKind, TyDef, Binding,
+    PatternKind, Pattern, Let, Stmt, Block, IfBranch, If, MatchArm, Match,
+    WhenArm, When, For, Lambda, ExprKind, Expr, Receiver, FnDef, ConstDef, Method,
+    Impl, Module, Import, ItemKind, Item, SourceFile,
+}
+
+impl Pretty for DelimArgs {
+    fn pretty(&self, lines: Lines) -> Tree {
+        let head = match self.delim {
+            Delimiter::Paren => "paren",
+            Delimiter::Bracket => "bracket",
+        };
+        Tree::list(head).with_all(all(&self.tokens, lines))
+    }
+}
+
+impl Pretty for TokenTree {
+    fn pretty(&self, lines: Lines) -> Tree {
         match self {
-            TyKind::Path(p) => write!(f, "{p}"),
-            TyKind::Tuple(t) => write!(f, "{t}"),
-            TyKind::Array(a) => write!(f, "{a}"),
+            // string and char tokens hold their text without quotes
+            TokenTree::Token(t) if t.kind() == Kind::StrLit => {
+                Tree::atom(format_args!("\"{}\"", t.repr()))
+            }
+            TokenTree::Token(t) if t.kind() == Kind::CharLit => {
+                Tree::atom(format_args!("'{}'", t.repr()))
+            }
+            TokenTree::Token(t) => Tree::atom(t.repr()),
+            TokenTree::Delimited(d) => d.pretty(lines),
         }
     }
 }
 
-impl Display for Ty {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.kind)
+impl Pretty for Attribute {
+    fn pretty(&self, lines: Lines) -> Tree {
+        let head = match self.style {
+            AttrStyle::Outer => "attr",
+            AttrStyle::Inner => "inner-attr",
+        };
+        Tree::list(head)
+            .with(self.path.pretty(lines))
+            .with_all(all(&self.args, lines))
+            .at(self.span, lines)
     }
 }
 
-impl Display for Variant {
+impl Display for LitKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "(variant {}", self.name)?;
-        if !self.data.is_empty() {
-            for ty in self.data.iter() {
-                write!(f, " {ty}")?;
+        match self {
+            LitKind::Int { value, suffix } => {
+                write!(f, "{value}{}", suffix.map_or("", IntTy::suffix))
+            }
+            LitKind::Float { value, suffix } => {
+                write!(f, "{value:?}{}", suffix.map_or("", FloatTy::suffix))
             }
+            LitKind::Complex { re, im } => write!(f, "{re:?}+{im:?}i"),
+            LitKind::Char(c) => write!(f, "{c:?}"),
+            LitKind::Str(s) => write!(f, "{:?}", interner::get(*s)),
         }
-        write!(f, ")")
     }
 }
 
-impl Display for Enum {
+impl Pretty for Lit {
+    fn pretty(&self, _: Lines) -> Tree {
+        Tree::list("lit").with(Tree::atom(self.kind))
+    }
+}
+
+impl Display for Visibility {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "(enum")?;
-        for variant in &self.variants {
-            write!(f, " {variant}")?;
+        match self {
+            Visibility::Private => Ok(()),
+            Visibility::Public(_) => write!(f, "pub "),
         }
-        write!(f, ")")
     }
 }
 
-impl Display for Param {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "(param {} {})", self.name, self.ty)
+/// The `pub` of public items, if any.
+fn vis(vis: &Visibility) -> Option<Tree> {
+    match vis {
+        Visibility::Private => None,
+        Visibility::Public(_) => Some(Tree::atom("pub")),
     }
 }
 
-impl Display for Record {
+impl Display for Ident {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "(record")?;
-        for field in &self.fields {
-            write!(f, " {field}")?;
+        f.write_str(interner::get(self.name))
+    }
+}
+
+impl Pretty for Path {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("path")
+            .with_all(self.segments.iter().map(Tree::atom))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Tuple {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("tup")
+            .with_all(all(&self.types, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Array {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("array")
+            .with(self.ty.pretty(lines))
+            .with(self.len.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for FnTy {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("fn")
+            .with(self.param.pretty(lines))
+            .with(self.ret.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for TyKind {
+    fn pretty(&self, lines: Lines) -> Tree {
+        match self {
+            TyKind::Path(p) => p.pretty(lines),
+            TyKind::Tuple(t) => t.pretty(lines),
+            TyKind::Array(a) => a.pretty(lines),
+            TyKind::Fn(t) => t.pretty(lines),
+            TyKind::RawPtr(t) => Tree::list("raw").with(t.pretty(lines)),
+            TyKind::Ref { mutable: false, ty } => Tree::list("ref").with(ty.pretty(lines)),
+            TyKind::Ref { mutable: true, ty } => Tree::list("ref-mut").with(ty.pretty(lines)),
+            TyKind::Never => Tree::list("never"),
+        }
+    }
+}
+
+impl Pretty for Ty {
+    fn pretty(&self, lines: Lines) -> Tree {
+        self.kind.pretty(lines).at(self.span, lines)
+    }
+}
+
+impl Pretty for Variant {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("variant")
+            .with_all(all(&self.attrs, lines))
+            .with(Tree::atom(&self.name))
+            .with_all(all(&self.data, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Enum {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("enum")
+            .with_all(all(&self.variants, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Param {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::atom(&self.name).at(self.span, lines)
+    }
+}
+
+impl Pretty for ParamGroup {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("param")
+            .with_all(all(&self.attrs, lines))
+            .with_all(vis(&self.vis))
+            .with_all(all(&self.params, lines))
+            .with(self.ty.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Record {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("record")
+            .with_all(all(&self.fields, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for TyDefKind {
+    fn pretty(&self, lines: Lines) -> Tree {
+        match self {
+            TyDefKind::Alias(t) => t.pretty(lines),
+            TyDefKind::Record(r) => r.pretty(lines),
+            TyDefKind::Enum(e) => e.pretty(lines),
+        }
+    }
+}
+
+impl Pretty for TyDef {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("typedef")
+            .with_all(vis(&self.vis))
+            .with(Tree::atom(&self.name))
+            .with(self.ty.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Binding {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("bind")
+            .with_all(self.mutable.then(|| Tree::atom("mut")))
+            .with(Tree::atom(&self.name))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for PatternKind {
+    fn pretty(&self, lines: Lines) -> Tree {
+        match self {
+            PatternKind::Wild => Tree::list("wild"),
+            PatternKind::Binding(b) => b.pretty(lines),
+            PatternKind::Lit(l) => l.pretty(lines),
+            PatternKind::Neg(l) => Tree::list("neg").with(l.pretty(lines)),
+            PatternKind::Tuple(pats) => Tree::list("tup").with_all(all(pats, lines)),
+            PatternKind::Array(pats) => Tree::list("array").with_all(all(pats, lines)),
+            PatternKind::Ctor(path, args) => Tree::list("ctor")
+                .with(path.pretty(lines))
+                .with_all(all(args, lines)),
         }
-        write!(f, ")")
     }
 }
 
-impl Display for TyDefKind {
+impl Pretty for Pattern {
+    fn pretty(&self, lines: Lines) -> Tree {
+        self.kind.pretty(lines).at(self.span, lines)
+    }
+}
+
+impl Display for UnOp {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.head())
+    }
+}
+
+impl UnOp {
+    fn head(self) -> &'static str {
         match self {
-            TyDefKind::Alias(t) => write!(f, "{t}"),
-            TyDefKind::Record(r) => write!(f, "{r}"),
-            TyDefKind::Enum(e) => write!(f, "{e}"),
+            UnOp::Neg => "neg",
+            UnOp::Deref => "deref",
+            UnOp::Ref => "ref",
+            UnOp::RefMut => "ref-mut",
+            UnOp::Raw => "raw",
         }
     }
 }
 
-impl Display for TyDef {
+impl Display for BinOp {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "(typedef {} {})", self.name, self.ty)
+        f.write_str(self.head())
+    }
+}
+
+impl BinOp {
+    fn head(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Eq => "=",
+            BinOp::Neq => "/=",
+            BinOp::Lt => "<",
+            BinOp::Gt => ">",
+            BinOp::LtEq => "<=",
+            BinOp::GtEq => ">=",
+        }
+    }
+}
+
+impl Pretty for Let {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("let")
+            .with(self.pat.pretty(lines))
+            .with_all(all(&self.ty, lines))
+            .with(self.init.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Stmt {
+    fn pretty(&self, lines: Lines) -> Tree {
+        match &self.kind {
+            StmtKind::Let(l) => l.pretty(lines),
+            StmtKind::Expr(e) => Tree::list("stmt").with(e.pretty(lines)),
+        }
+        .at(self.span, lines)
+    }
+}
+
+impl Pretty for Block {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("block")
+            .with_all(all(&self.stmts, lines))
+            .with_all(all(&self.expr, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for IfBranch {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("branch")
+            .with(self.cond.pretty(lines))
+            .with(self.body.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+/// The `(else ...)` of `if` and `when`.
+fn else_branch(else_branch: &Option<Block>, lines: Lines) -> Option<Tree> {
+    let block = else_branch.as_ref()?;
+    Some(Tree::list("else").with(block.pretty(lines)))
+}
+
+impl Pretty for If {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("if")
+            .with_all(all(&self.branches, lines))
+            .with_all(else_branch(&self.else_branch, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for MatchArm {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("arm")
+            .with(self.pat.pretty(lines))
+            .with(self.body.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Match {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("match")
+            .with(self.scrutinee.pretty(lines))
+            .with_all(all(&self.arms, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for WhenArm {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("arm")
+            .with(self.cond.pretty(lines))
+            .with(self.body.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for When {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("when")
+            .with_all(all(&self.arms, lines))
+            .with_all(else_branch(&self.else_branch, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for For {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("for")
+            .with(self.pat.pretty(lines))
+            .with(self.iter.pretty(lines))
+            .with(self.body.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Lambda {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("lambda")
+            .with_all(all(&self.params, lines))
+            .with(self.body.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for ExprKind {
+    fn pretty(&self, lines: Lines) -> Tree {
+        match self {
+            ExprKind::Lit(l) => l.pretty(lines),
+            ExprKind::Path(p) => p.pretty(lines),
+            ExprKind::Tuple(exprs) => Tree::list("tup").with_all(all(exprs, lines)),
+            ExprKind::Call(callee, args) => Tree::list("call")
+                .with(callee.pretty(lines))
+                .with_all(all(args, lines)),
+            ExprKind::Unary(op, e) => Tree::list(op.head()).with(e.pretty(lines)),
+            ExprKind::Binary(op, lhs, rhs) => Tree::list(op.head())
+                .with(lhs.pretty(lines))
+                .with(rhs.pretty(lines)),
+            ExprKind::Assign(place, value) => Tree::list("assign")
+                .with(place.pretty(lines))
+                .with(value.pretty(lines)),
+            ExprKind::Is(e, pat) => Tree::list("is")
+                .with(e.pretty(lines))
+                .with(pat.pretty(lines)),
+            ExprKind::Block(b) => b.pretty(lines),
+            ExprKind::If(i) => i.pretty(lines),
+            ExprKind::Match(m) => m.pretty(lines),
+            ExprKind::When(w) => w.pretty(lines),
+            ExprKind::Loop(b) => Tree::list("loop").with(b.pretty(lines)),
+            ExprKind::For(l) => l.pretty(lines),
+            ExprKind::Break => Tree::list("break"),
+            ExprKind::Continue => Tree::list("continue"),
+            ExprKind::Return(e) => Tree::list("return").with_all(all(e, lines)),
+            ExprKind::Lambda(l) => l.pretty(lines),
+        }
+    }
+}
+
+impl Pretty for Expr {
+    fn pretty(&self, lines: Lines) -> Tree {
+        self.kind.pretty(lines).at(self.span, lines)
+    }
+}
+
+impl Pretty for Receiver {
+    fn pretty(&self, lines: Lines) -> Tree {
+        let kind = match self.kind {
+            ReceiverKind::Value => "value",
+            ReceiverKind::MutValue => "mut",
+            ReceiverKind::Ref => "ref",
+            ReceiverKind::RefMut => "ref-mut",
+        };
+        Tree::list("self")
+            .with(Tree::atom(kind))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for FnDef {
+    fn pretty(&self, lines: Lines) -> Tree {
+        let ret = self
+            .ret
+            .as_ref()
+            .map(|ret| Tree::list("ret").with(ret.pretty(lines)));
+        Tree::list("fun")
+            .with_all(vis(&self.vis))
+            .with(Tree::atom(&self.name))
+            .with_all(all(&self.receiver, lines))
+            .with_all(all(&self.params, lines))
+            .with_all(ret)
+            .with(self.body.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for ConstDef {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("const")
+            .with_all(vis(&self.vis))
+            .with(Tree::atom(&self.name))
+            .with_all(all(&self.ty, lines))
+            .with(self.value.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Method {
+    fn pretty(&self, lines: Lines) -> Tree {
+        if self.attrs.is_empty() {
+            return self.fn_def.pretty(lines).at(self.span, lines);
+        }
+
+        Tree::list("method")
+            .with_all(all(&self.attrs, lines))
+            .with(self.fn_def.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Impl {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("impl")
+            .with(self.target.pretty(lines))
+            .with_all(all(&self.methods, lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Module {
+    fn pretty(&self, lines: Lines) -> Tree {
+        let items = self.items.as_ref().map(|items| {
+            Tree::list("items")
+                .with_all(all(&self.attrs, lines))
+                .with_all(all(items, lines))
+        });
+        Tree::list("module")
+            .with(Tree::atom(&self.name))
+            .with_all(items)
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for Import {
+    fn pretty(&self, lines: Lines) -> Tree {
+        let kind = match &self.kind {
+            ImportKind::Single(Some(rename)) => Some(Tree::list("as").with(Tree::atom(rename))),
+            ImportKind::Single(None) => None,
+            ImportKind::Glob => Some(Tree::atom("*")),
+        };
+        Tree::list("import")
+            .with(self.path.pretty(lines))
+            .with_all(kind)
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for ItemKind {
+    fn pretty(&self, lines: Lines) -> Tree {
+        match self {
+            ItemKind::TyDef(t) => t.pretty(lines),
+            ItemKind::FnDef(d) => d.pretty(lines),
+            ItemKind::ConstDef(c) => c.pretty(lines),
+            ItemKind::Impl(i) => i.pretty(lines),
+            ItemKind::Module(m) => m.pretty(lines),
+            ItemKind::Import(i) => i.pretty(lines),
+        }
+    }
+}
+
+impl Pretty for Item {
+    fn pretty(&self, lines: Lines) -> Tree {
+        if self.attrs.is_empty() {
+            return self.kind.pretty(lines).at(self.span, lines);
+        }
+
+        Tree::list("item")
+            .with_all(all(&self.attrs, lines))
+            .with(self.kind.pretty(lines))
+            .at(self.span, lines)
+    }
+}
+
+impl Pretty for SourceFile {
+    fn pretty(&self, lines: Lines) -> Tree {
+        Tree::list("file")
+            .with_all(all(&self.attrs, lines))
+            .with_all(all(&self.items, lines))
+            .at(self.span, lines)
     }
 }
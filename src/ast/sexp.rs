@@ -0,0 +1,968 @@
+//! Reads the s-expressions printed by the `Display` impls of the AST, or laid
+//! out by a [`Printer`](super::pretty::Printer) without spans, back into
+//! nodes, e.g. to write test expectations as s-expressions.
+//!
+//! The printed format doesn't keep spans, so nodes read back have empty
+//! spans and fresh ids. Compare them with
+//! [`StructuralEq`](super::StructuralEq).
+
+use super::*;
+use crate::{interner::intern, lexer::Scanner, token::Kind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// Unbalanced parentheses or an unterminated literal, at the byte offset.
+    Syntax(usize),
+    /// The s-expression at the byte offset isn't the expected node.
+    Expected(usize, &'static str),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Reads a node from its printed form.
+pub fn read<T: FromSexp>(text: &str) -> Result<T> {
+    let sexp = Sexp::parse(text)?;
+    T::from_sexp(&sexp, &mut Reader::default())
+}
+
+/// An atom or a list, with the byte offset it starts at.
+#[derive(Clone, Debug)]
+pub enum Sexp<'a> {
+    Atom(usize, &'a str),
+    List(usize, Vec<Sexp<'a>>),
+}
+
+impl<'a> Sexp<'a> {
+    pub fn parse(text: &'a str) -> Result<Self> {
+        let mut pos = 0;
+        let sexp = Self::parse_at(text, &mut pos)?;
+        skip_whitespace(text, &mut pos);
+        if pos < text.len() {
+            return Err(Error::Syntax(pos));
+        }
+        Ok(sexp)
+    }
+
+    fn parse_at(text: &'a str, pos: &mut usize) -> Result<Self> {
+        skip_whitespace(text, pos);
+        let start = *pos;
+        let rest = &text[start..];
+        match rest.chars().next() {
+            Some('(') => {
+                *pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    skip_whitespace(text, pos);
+                    match text[*pos..].chars().next() {
+                        Some(')') => break,
+                        Some(_) => items.push(Self::parse_at(text, pos)?),
+                        None => return Err(Error::Syntax(start)),
+                    }
+                }
+                *pos += 1;
+                Ok(Sexp::List(start, items))
+            }
+            Some(')') | None => Err(Error::Syntax(start)),
+            // literals may hold spaces and parentheses
+            Some(quote @ ('"' | '\'')) => {
+                let mut escaped = false;
+                let len = rest[1..]
+                    .find(|c| {
+                        let end = c == quote && !escaped;
+                        escaped = c == '\\' && !escaped;
+                        end
+                    })
+                    .ok_or(Error::Syntax(start))?;
+                *pos += len + 2;
+                Ok(Sexp::Atom(start, &text[start..*pos]))
+            }
+            Some(_) => {
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .unwrap_or(rest.len());
+                *pos += len;
+                Ok(Sexp::Atom(start, &text[start..*pos]))
+            }
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        match self {
+            Sexp::Atom(offset, _) | Sexp::List(offset, _) => *offset,
+        }
+    }
+
+    /// The first atom of a list.
+    pub fn head(&self) -> Option<&'a str> {
+        match self {
+            Sexp::List(_, items) => match items.first() {
+                Some(Sexp::Atom(_, head)) => Some(head),
+                _ => None,
+            },
+            Sexp::Atom(..) => None,
+        }
+    }
+}
+
+fn skip_whitespace(text: &str, pos: &mut usize) {
+    *pos = text[*pos..]
+        .find(|c: char| !c.is_whitespace())
+        .map_or(text.len(), |len| *pos + len);
+}
+
+/// Hands out node ids.
+#[derive(Default)]
+pub struct Reader {
+    next_id: u32,
+}
+
+impl Reader {
+    fn id(&mut self) -> NodeId {
+        let id = NodeId::new(self.next_id);
+        self.next_id += 1;
+        id
+    }
+}
+
+pub trait FromSexp: Sized {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self>;
+}
+
+/// The items of a list after its head.
+struct List<'s, 'a> {
+    end: usize,
+    items: &'s [Sexp<'a>],
+}
+
+impl<'s, 'a> List<'s, 'a> {
+    /// Opens a list starting with `head`.
+    fn open(sexp: &'s Sexp<'a>, head: &'static str) -> Result<Self> {
+        match sexp {
+            Sexp::List(offset, items) if sexp.head() == Some(head) => Ok(List {
+                end: *offset,
+                items: &items[1..],
+            }),
+            _ => Err(Error::Expected(sexp.offset(), head)),
+        }
+    }
+
+    fn peek(&self) -> Option<&'s Sexp<'a>> {
+        self.items.first()
+    }
+
+    fn peek_head(&self) -> Option<&'a str> {
+        self.peek().and_then(Sexp::head)
+    }
+
+    fn peek_atom(&self) -> Option<&'a str> {
+        match self.peek() {
+            Some(Sexp::Atom(_, atom)) => Some(atom),
+            _ => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn next(&mut self, what: &'static str) -> Result<&'s Sexp<'a>> {
+        let (first, rest) = self
+            .items
+            .split_first()
+            .ok_or(Error::Expected(self.end, what))?;
+        self.items = rest;
+        Ok(first)
+    }
+
+    fn atom(&mut self, what: &'static str) -> Result<&'a str> {
+        match self.next(what)? {
+            Sexp::Atom(_, atom) => Ok(atom),
+            sexp => Err(Error::Expected(sexp.offset(), what)),
+        }
+    }
+
+    fn eat_atom(&mut self, atom: &str) -> bool {
+        let found = self.peek_atom() == Some(atom);
+        if found {
+            self.items = &self.items[1..];
+        }
+        found
+    }
+
+    fn read<T: FromSexp>(&mut self, r: &mut Reader) -> Result<T> {
+        match self.peek() {
+            Some(sexp) => {
+                self.items = &self.items[1..];
+                T::from_sexp(sexp, r)
+            }
+            None => Err(Error::Expected(self.end, "a node")),
+        }
+    }
+
+    /// Reads items as long as they are lists starting with one of `heads`.
+    fn read_while<T: FromSexp>(&mut self, r: &mut Reader, heads: &[&str]) -> Result<Vec<T>> {
+        let mut nodes = Vec::new();
+        while self.peek_head().is_some_and(|head| heads.contains(&head)) {
+            nodes.push(self.read(r)?);
+        }
+        Ok(nodes)
+    }
+
+    fn read_rest<T: FromSexp>(&mut self, r: &mut Reader) -> Result<Vec<T>> {
+        let mut nodes = Vec::new();
+        while !self.items.is_empty() {
+            nodes.push(self.read(r)?);
+        }
+        Ok(nodes)
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.items.first() {
+            Some(sexp) => Err(Error::Expected(sexp.offset(), "the end of the list")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Opens a list whose head was already matched.
+fn open<'s, 'a>(sexp: &'s Sexp<'a>) -> List<'s, 'a> {
+    match sexp {
+        Sexp::List(offset, items) => List {
+            end: *offset,
+            items: &items[1..],
+        },
+        Sexp::Atom(..) => unreachable!("only lists have a head"),
+    }
+}
+
+fn vis(list: &mut List) -> Visibility {
+    if list.eat_atom("pub") {
+        Visibility::Public(Span::default())
+    } else {
+        Visibility::Private
+    }
+}
+
+impl<T: FromSexp> FromSexp for P<T> {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        T::from_sexp(sexp, r).map(P::new)
+    }
+}
+
+impl FromSexp for Ident {
+    fn from_sexp(sexp: &Sexp, _: &mut Reader) -> Result<Self> {
+        match sexp {
+            Sexp::Atom(_, name) => Ok(Ident::new(Span::default(), intern(*name))),
+            _ => Err(Error::Expected(sexp.offset(), "an identifier")),
+        }
+    }
+}
+
+impl FromSexp for TokenTree {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let Sexp::Atom(offset, text) = *sexp else {
+            return DelimArgs::from_sexp(sexp, r).map(TokenTree::Delimited);
+        };
+
+        // string and char tokens hold their text without quotes
+        let quoted = |quote| {
+            text.strip_prefix(quote)
+                .and_then(|text| text.strip_suffix(quote))
+        };
+        let token = if let Some(s) = quoted('"') {
+            Token::new(Kind::StrLit, Span::default(), Some(intern(s)))
+        } else if let Some(c) = quoted('\'') {
+            Token::new(Kind::CharLit, Span::default(), Some(intern(c)))
+        } else {
+            match Scanner::new(text).scan() {
+                Some(token) if token.repr() == text => {
+                    Token::new(token.kind(), Span::default(), token.symbol())
+                }
+                _ => return Err(Error::Expected(offset, "a token")),
+            }
+        };
+        Ok(TokenTree::Token(token))
+    }
+}
+
+impl FromSexp for DelimArgs {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let (delim, head) = match sexp.head() {
+            Some("bracket") => (Delimiter::Bracket, "bracket"),
+            _ => (Delimiter::Paren, "paren"),
+        };
+        let mut list = List::open(sexp, head)?;
+        let tokens = list.read_rest(r)?;
+        Ok(DelimArgs::new(Span::default(), delim, tokens))
+    }
+}
+
+impl FromSexp for Attribute {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let (style, head) = match sexp.head() {
+            Some("inner-attr") => (AttrStyle::Inner, "inner-attr"),
+            _ => (AttrStyle::Outer, "attr"),
+        };
+        let mut list = List::open(sexp, head)?;
+        let path = list.read(r)?;
+        let args = if list.len() > 0 {
+            Some(list.read(r)?)
+        } else {
+            None
+        };
+        list.finish()?;
+        Ok(Attribute::new(r.id(), Span::default(), style, path, args))
+    }
+}
+
+/// Reads the literals printed by `LitKind`'s `Display`, whose chars and
+/// strings are escaped as in Rust.
+fn lit_kind(text: &str) -> Option<LitKind> {
+    if let Some(c) = text.strip_prefix('\'').and_then(|c| c.strip_suffix('\'')) {
+        let c = unescape(c)?;
+        let mut chars = c.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(LitKind::Char(c)),
+            _ => None,
+        };
+    }
+    if let Some(s) = text.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        return Some(LitKind::Str(intern(unescape(s)?)));
+    }
+    if let Some((re, im)) = text.strip_suffix('i').and_then(|c| c.split_once('+')) {
+        return Some(LitKind::Complex {
+            re: re.parse().ok()?,
+            im: im.parse().ok()?,
+        });
+    }
+
+    let (value, suffix) = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .filter(|&i| text[i..] != *"inf" && text[i..] != *"NaN" && text.as_bytes()[i] != b'e')
+        .map_or((text, ""), |i| text.split_at(i));
+    if let Some(suffix) = IntTy::from_suffix(suffix) {
+        return Some(LitKind::Int {
+            value: value.parse().ok()?,
+            suffix: Some(suffix),
+        });
+    }
+    let float_suffix = FloatTy::from_suffix(suffix);
+    if float_suffix.is_none() && !suffix.is_empty() {
+        return None;
+    }
+    if float_suffix.is_none() && value.bytes().all(|b| b.is_ascii_digit()) {
+        return Some(LitKind::Int {
+            value: value.parse().ok()?,
+            suffix: None,
+        });
+    }
+    Some(LitKind::Float {
+        value: value.parse().ok()?,
+        suffix: float_suffix,
+    })
+}
+
+/// Decodes the escapes of Rust's `Debug` output.
+fn unescape(s: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            c @ ('\\' | '\'' | '"') => c,
+            'u' => {
+                let rest = chars.as_str().strip_prefix('{')?;
+                let (hex, rest) = rest.split_once('}')?;
+                chars = rest.chars();
+                char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+            }
+            _ => return None,
+        });
+    }
+    Some(out)
+}
+
+impl FromSexp for Lit {
+    fn from_sexp(sexp: &Sexp, _: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "lit")?;
+        let value = list.next("a literal")?;
+        let kind = match value {
+            Sexp::Atom(_, text) => lit_kind(text),
+            Sexp::List(..) => None,
+        }
+        .ok_or(Error::Expected(value.offset(), "a literal"))?;
+        list.finish()?;
+        Ok(Lit::new(Span::default(), kind))
+    }
+}
+
+impl FromSexp for Path {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "path")?;
+        let segments = list.read_rest(r)?;
+        Ok(Path::new(r.id(), Span::default(), segments))
+    }
+}
+
+impl FromSexp for Tuple {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "tup")?;
+        let types = list.read_rest(r)?;
+        Ok(Tuple::new(r.id(), Span::default(), types))
+    }
+}
+
+impl FromSexp for Array {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "array")?;
+        let ty = list.read(r)?;
+        let len = list.read(r)?;
+        list.finish()?;
+        Ok(Array::new(r.id(), Span::default(), ty, len))
+    }
+}
+
+impl FromSexp for FnTy {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "fn")?;
+        let param = list.read(r)?;
+        let ret = list.read(r)?;
+        list.finish()?;
+        Ok(FnTy::new(r.id(), Span::default(), param, ret))
+    }
+}
+
+impl FromSexp for Ty {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let kind = match sexp.head() {
+            Some("path") => TyKind::Path(Path::from_sexp(sexp, r)?),
+            Some("tup") => TyKind::Tuple(Tuple::from_sexp(sexp, r)?),
+            Some("array") => TyKind::Array(Array::from_sexp(sexp, r)?),
+            Some("fn") => TyKind::Fn(FnTy::from_sexp(sexp, r)?),
+            Some(head @ ("raw" | "ref" | "ref-mut")) => {
+                let mut list = open(sexp);
+                let ty = list.read(r)?;
+                list.finish()?;
+                if head == "raw" {
+                    TyKind::RawPtr(ty)
+                } else {
+                    TyKind::Ref {
+                        mutable: head == "ref-mut",
+                        ty,
+                    }
+                }
+            }
+            Some("never") => {
+                List::open(sexp, "never")?.finish()?;
+                TyKind::Never
+            }
+            _ => return Err(Error::Expected(sexp.offset(), "a type")),
+        };
+        Ok(Ty::new(r.id(), Span::default(), kind))
+    }
+}
+
+impl FromSexp for Variant {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "variant")?;
+        let attrs = list.read_while(r, &["attr"])?;
+        let name = list.read(r)?;
+        let data = list.read_rest(r)?;
+        Ok(Variant::new(r.id(), Span::default(), attrs, name, data))
+    }
+}
+
+impl FromSexp for Enum {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "enum")?;
+        let variants = list.read_rest(r)?;
+        Ok(Enum::new(r.id(), Span::default(), variants))
+    }
+}
+
+impl FromSexp for ParamGroup {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "param")?;
+        let attrs = list.read_while(r, &["attr"])?;
+        let vis = vis(&mut list);
+        let mut params = Vec::new();
+        while list.peek_atom().is_some() {
+            let name = list.read(r)?;
+            params.push(Param::new(r.id(), Span::default(), name));
+        }
+        let ty = list.read(r)?;
+        list.finish()?;
+        Ok(ParamGroup::new(
+            r.id(),
+            Span::default(),
+            attrs,
+            vis,
+            params,
+            ty,
+        ))
+    }
+}
+
+impl FromSexp for Record {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "record")?;
+        let fields = list.read_rest(r)?;
+        Ok(Record::new(r.id(), Span::default(), fields))
+    }
+}
+
+impl FromSexp for TyDef {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "typedef")?;
+        let vis = vis(&mut list);
+        let name = list.read(r)?;
+        let ty = match list.peek_head() {
+            Some("record") => TyDefKind::Record(list.read(r)?),
+            Some("enum") => TyDefKind::Enum(list.read(r)?),
+            _ => TyDefKind::Alias(list.read(r)?),
+        };
+        list.finish()?;
+        Ok(TyDef::new(r.id(), Span::default(), vis, name, ty))
+    }
+}
+
+impl FromSexp for Binding {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "bind")?;
+        let mutable = list.len() > 1 && list.eat_atom("mut");
+        let name = list.read(r)?;
+        list.finish()?;
+        Ok(Binding::new(r.id(), Span::default(), mutable, name))
+    }
+}
+
+impl FromSexp for Pattern {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let kind = match sexp.head() {
+            Some("wild") => {
+                List::open(sexp, "wild")?.finish()?;
+                PatternKind::Wild
+            }
+            Some("bind") => PatternKind::Binding(Binding::from_sexp(sexp, r)?),
+            Some("lit") => PatternKind::Lit(Lit::from_sexp(sexp, r)?),
+            Some("neg") => {
+                let mut list = List::open(sexp, "neg")?;
+                let lit = list.read(r)?;
+                list.finish()?;
+                PatternKind::Neg(lit)
+            }
+            Some("tup") => PatternKind::Tuple(List::open(sexp, "tup")?.read_rest(r)?),
+            Some("array") => PatternKind::Array(List::open(sexp, "array")?.read_rest(r)?),
+            Some("ctor") => {
+                let mut list = List::open(sexp, "ctor")?;
+                let path = list.read(r)?;
+                PatternKind::Ctor(path, list.read_rest(r)?)
+            }
+            _ => return Err(Error::Expected(sexp.offset(), "a pattern")),
+        };
+        Ok(Pattern::new(r.id(), Span::default(), kind))
+    }
+}
+
+impl FromSexp for Let {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "let")?;
+        let pat = list.read(r)?;
+        let ty = if list.len() > 1 {
+            Some(list.read(r)?)
+        } else {
+            None
+        };
+        let init = list.read(r)?;
+        list.finish()?;
+        Ok(Let::new(r.id(), Span::default(), pat, ty, init))
+    }
+}
+
+impl FromSexp for Stmt {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let kind = if sexp.head() == Some("let") {
+            StmtKind::Let(Let::from_sexp(sexp, r)?)
+        } else {
+            let mut list = List::open(sexp, "stmt")?;
+            let expr = list.read(r)?;
+            list.finish()?;
+            StmtKind::Expr(expr)
+        };
+        Ok(Stmt::new(r.id(), Span::default(), kind))
+    }
+}
+
+impl FromSexp for Block {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "block")?;
+        let stmts = list.read_while(r, &["let", "stmt"])?;
+        let expr = if list.len() > 0 {
+            Some(list.read(r)?)
+        } else {
+            None
+        };
+        list.finish()?;
+        Ok(Block::new(r.id(), Span::default(), stmts, expr))
+    }
+}
+
+/// Reads `(else block)` if it ends the list.
+fn else_branch(list: &mut List, r: &mut Reader) -> Result<Option<Block>> {
+    if list.peek_head() != Some("else") {
+        return Ok(None);
+    }
+    let mut else_list = List::open(list.next("else")?, "else")?;
+    let block = else_list.read(r)?;
+    else_list.finish()?;
+    Ok(Some(block))
+}
+
+impl FromSexp for IfBranch {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "branch")?;
+        let cond = list.read(r)?;
+        let body = list.read(r)?;
+        list.finish()?;
+        Ok(IfBranch::new(r.id(), Span::default(), cond, body))
+    }
+}
+
+impl FromSexp for If {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "if")?;
+        let branches = list.read_while(r, &["branch"])?;
+        let else_branch = else_branch(&mut list, r)?;
+        list.finish()?;
+        Ok(If::new(r.id(), Span::default(), branches, else_branch))
+    }
+}
+
+impl FromSexp for MatchArm {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "arm")?;
+        let pat = list.read(r)?;
+        let body = list.read(r)?;
+        list.finish()?;
+        Ok(MatchArm::new(r.id(), Span::default(), pat, body))
+    }
+}
+
+impl FromSexp for Match {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "match")?;
+        let scrutinee = list.read(r)?;
+        let arms = list.read_rest(r)?;
+        Ok(Match::new(r.id(), Span::default(), scrutinee, arms))
+    }
+}
+
+impl FromSexp for WhenArm {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "arm")?;
+        let cond = list.read(r)?;
+        let body = list.read(r)?;
+        list.finish()?;
+        Ok(WhenArm::new(r.id(), Span::default(), cond, body))
+    }
+}
+
+impl FromSexp for When {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "when")?;
+        let arms = list.read_while(r, &["arm"])?;
+        let else_branch = else_branch(&mut list, r)?;
+        list.finish()?;
+        Ok(When::new(r.id(), Span::default(), arms, else_branch))
+    }
+}
+
+impl FromSexp for For {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "for")?;
+        let pat = list.read(r)?;
+        let iter = list.read(r)?;
+        let body = list.read(r)?;
+        list.finish()?;
+        Ok(For::new(r.id(), Span::default(), pat, iter, body))
+    }
+}
+
+impl FromSexp for Lambda {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "lambda")?;
+        let params = list.read_while(r, &["param"])?;
+        let body = list.read(r)?;
+        list.finish()?;
+        Ok(Lambda::new(r.id(), Span::default(), params, body))
+    }
+}
+
+fn un_op(head: &str) -> Option<UnOp> {
+    Some(match head {
+        "neg" => UnOp::Neg,
+        "deref" => UnOp::Deref,
+        "ref" => UnOp::Ref,
+        "ref-mut" => UnOp::RefMut,
+        "raw" => UnOp::Raw,
+        _ => return None,
+    })
+}
+
+fn bin_op(head: &str) -> Option<BinOp> {
+    Some(match head {
+        "+" => BinOp::Add,
+        "-" => BinOp::Sub,
+        "*" => BinOp::Mul,
+        "/" => BinOp::Div,
+        "=" => BinOp::Eq,
+        "/=" => BinOp::Neq,
+        "<" => BinOp::Lt,
+        ">" => BinOp::Gt,
+        "<=" => BinOp::LtEq,
+        ">=" => BinOp::GtEq,
+        _ => return None,
+    })
+}
+
+impl FromSexp for Expr {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let Some(head) = sexp.head() else {
+            return Err(Error::Expected(sexp.offset(), "an expression"));
+        };
+        let mut list = open(sexp);
+        let kind = match head {
+            "lit" => ExprKind::Lit(Lit::from_sexp(sexp, r)?),
+            "path" => ExprKind::Path(Path::from_sexp(sexp, r)?),
+            "tup" => ExprKind::Tuple(list.read_rest(r)?),
+            "call" => {
+                let callee = list.read(r)?;
+                ExprKind::Call(callee, list.read_rest(r)?)
+            }
+            "assign" => {
+                let place = list.read(r)?;
+                let value = list.read(r)?;
+                list.finish()?;
+                ExprKind::Assign(place, value)
+            }
+            "is" => {
+                let expr = list.read(r)?;
+                let pat = list.read(r)?;
+                list.finish()?;
+                ExprKind::Is(expr, pat)
+            }
+            "block" => ExprKind::Block(Block::from_sexp(sexp, r)?),
+            "if" => ExprKind::If(If::from_sexp(sexp, r)?),
+            "match" => ExprKind::Match(Match::from_sexp(sexp, r)?),
+            "when" => ExprKind::When(When::from_sexp(sexp, r)?),
+            "loop" => {
+                let body = list.read(r)?;
+                list.finish()?;
+                ExprKind::Loop(body)
+            }
+            "for" => ExprKind::For(For::from_sexp(sexp, r)?),
+            "break" => {
+                list.finish()?;
+                ExprKind::Break
+            }
+            "continue" => {
+                list.finish()?;
+                ExprKind::Continue
+            }
+            "return" => {
+                let expr = if list.len() > 0 {
+                    Some(list.read(r)?)
+                } else {
+                    None
+                };
+                list.finish()?;
+                ExprKind::Return(expr)
+            }
+            "lambda" => ExprKind::Lambda(Lambda::from_sexp(sexp, r)?),
+            head => {
+                if let Some(op) = un_op(head) {
+                    let expr = list.read(r)?;
+                    list.finish()?;
+                    ExprKind::Unary(op, expr)
+                } else if let Some(op) = bin_op(head) {
+                    let lhs = list.read(r)?;
+                    let rhs = list.read(r)?;
+                    list.finish()?;
+                    ExprKind::Binary(op, lhs, rhs)
+                } else {
+                    return Err(Error::Expected(sexp.offset(), "an expression"));
+                }
+            }
+        };
+        Ok(Expr::new(r.id(), Span::default(), kind))
+    }
+}
+
+impl FromSexp for Receiver {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "self")?;
+        let kind = match list.atom("a receiver")? {
+            "value" => ReceiverKind::Value,
+            "mut" => ReceiverKind::MutValue,
+            "ref" => ReceiverKind::Ref,
+            "ref-mut" => ReceiverKind::RefMut,
+            _ => return Err(Error::Expected(sexp.offset(), "a receiver")),
+        };
+        list.finish()?;
+        Ok(Receiver::new(r.id(), Span::default(), kind))
+    }
+}
+
+impl FromSexp for FnDef {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "fun")?;
+        let vis = vis(&mut list);
+        let name = list.read(r)?;
+        let receiver = if list.peek_head() == Some("self") {
+            Some(list.read(r)?)
+        } else {
+            None
+        };
+        let params = list.read_while(r, &["param"])?;
+        let ret = if list.peek_head() == Some("ret") {
+            let mut ret = List::open(list.next("ret")?, "ret")?;
+            let ty = ret.read(r)?;
+            ret.finish()?;
+            Some(ty)
+        } else {
+            None
+        };
+        let body = list.read(r)?;
+        list.finish()?;
+        Ok(FnDef::new(
+            r.id(),
+            Span::default(),
+            vis,
+            name,
+            receiver,
+            params,
+            ret,
+            body,
+        ))
+    }
+}
+
+impl FromSexp for ConstDef {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "const")?;
+        let vis = vis(&mut list);
+        let name = list.read(r)?;
+        let ty = if list.len() > 1 {
+            Some(list.read(r)?)
+        } else {
+            None
+        };
+        let value = list.read(r)?;
+        list.finish()?;
+        Ok(ConstDef::new(r.id(), Span::default(), vis, name, ty, value))
+    }
+}
+
+impl FromSexp for Method {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let (attrs, fn_def) = if sexp.head() == Some("method") {
+            let mut list = open(sexp);
+            let attrs = list.read_while(r, &["attr"])?;
+            let fn_def = list.read(r)?;
+            list.finish()?;
+            (attrs, fn_def)
+        } else {
+            (Vec::new(), FnDef::from_sexp(sexp, r)?)
+        };
+        Ok(Method::new(r.id(), Span::default(), attrs, fn_def))
+    }
+}
+
+impl FromSexp for Impl {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "impl")?;
+        let target = list.read(r)?;
+        let methods = list.read_rest(r)?;
+        Ok(Impl::new(r.id(), Span::default(), target, methods))
+    }
+}
+
+impl FromSexp for Module {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "module")?;
+        let name = list.read(r)?;
+        let (attrs, items) = if list.len() > 0 {
+            let mut items = List::open(list.next("items")?, "items")?;
+            let attrs = items.read_while(r, &["inner-attr"])?;
+            (attrs, Some(items.read_rest(r)?))
+        } else {
+            (Vec::new(), None)
+        };
+        list.finish()?;
+        Ok(Module::new(r.id(), Span::default(), name, attrs, items))
+    }
+}
+
+impl FromSexp for Import {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "import")?;
+        let path = list.read(r)?;
+        let kind = if list.eat_atom("*") {
+            ImportKind::Glob
+        } else if list.len() > 0 {
+            let mut rename = List::open(list.next("as")?, "as")?;
+            let name = rename.read(r)?;
+            rename.finish()?;
+            ImportKind::Single(Some(name))
+        } else {
+            ImportKind::Single(None)
+        };
+        list.finish()?;
+        Ok(Import::new(r.id(), Span::default(), path, kind))
+    }
+}
+
+impl FromSexp for ItemKind {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        Ok(match sexp.head() {
+            Some("typedef") => ItemKind::TyDef(TyDef::from_sexp(sexp, r)?),
+            Some("fun") => ItemKind::FnDef(FnDef::from_sexp(sexp, r)?),
+            Some("const") => ItemKind::ConstDef(ConstDef::from_sexp(sexp, r)?),
+            Some("impl") => ItemKind::Impl(Impl::from_sexp(sexp, r)?),
+            Some("module") => ItemKind::Module(Module::from_sexp(sexp, r)?),
+            Some("import") => ItemKind::Import(Import::from_sexp(sexp, r)?),
+            _ => return Err(Error::Expected(sexp.offset(), "an item")),
+        })
+    }
+}
+
+impl FromSexp for Item {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let (attrs, kind) = if sexp.head() == Some("item") {
+            let mut list = open(sexp);
+            let attrs = list.read_while(r, &["attr"])?;
+            let kind = list.read(r)?;
+            list.finish()?;
+            (attrs, kind)
+        } else {
+            (Vec::new(), ItemKind::from_sexp(sexp, r)?)
+        };
+        Ok(Item::new(r.id(), Span::default(), attrs, kind))
+    }
+}
+
+impl FromSexp for SourceFile {
+    fn from_sexp(sexp: &Sexp, r: &mut Reader) -> Result<Self> {
+        let mut list = List::open(sexp, "file")?;
+        let attrs = list.read_while(r, &["inner-attr"])?;
+        let items = list.read_rest(r)?;
+        Ok(SourceFile::new(r.id(), Span::default(), attrs, items))
+    }
+}
@@ -0,0 +1,264 @@
+use super::*;
+
+/// Equality of syntax trees, ignoring spans and node ids, so that a parsed
+/// tree can be compared with one built by hand.
+pub trait StructuralEq {
+    fn structurally_eq(&self, other: &Self) -> bool;
+}
+
+impl<T: StructuralEq + ?Sized> StructuralEq for &T {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        (**self).structurally_eq(other)
+    }
+}
+
+impl<T: StructuralEq> StructuralEq for P<T> {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        (**self).structurally_eq(other)
+    }
+}
+
+impl<T: StructuralEq> StructuralEq for Option<T> {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.structurally_eq(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: StructuralEq> StructuralEq for [T] {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.structurally_eq(b))
+    }
+}
+
+impl<T: StructuralEq> StructuralEq for Vec<T> {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        self[..].structurally_eq(&other[..])
+    }
+}
+
+/// Leaves without spans, compared with `==`.
+macro_rules! eq_leaves {
+    ($($ty:ty),*) => {
+        $(
+            impl StructuralEq for $ty {
+                fn structurally_eq(&self, other: &Self) -> bool {
+                    self == other
+                }
+            }
+        )*
+    };
+}
+
+eq_leaves!(
+    bool,
+    Symbol,
+    Delimiter,
+    AttrStyle,
+    LitKind,
+    ReceiverKind,
+    UnOp,
+    BinOp
+);
+
+/// Compares the listed fields of a struct. Every other field must be named
+/// as ignored, so that new fields are not forgotten.
+macro_rules! eq_fields {
+    ($($ty:ident { $($ignored:ident),* ; $($field:ident),* })*) => {
+        $(
+            impl StructuralEq for $ty {
+                fn structurally_eq(&self, other: &Self) -> bool {
+                    let $ty { $($ignored: _,)* $($field: _,)* } = self;
+                    true $(&& self.$field.structurally_eq(&other.$field))*
+                }
+            }
+        )*
+    };
+}
+
+eq_fields! {
+    DelimArgs { span; delim, tokens }
+    Attribute { id, span; style, path, args }
+    Lit { span; kind }
+    Ident { span; name }
+    Path { id, span; segments }
+    Tuple { id, span; types }
+    Array { id, span; ty, len }
+    FnTy { id, span; param, ret }
+    Ty { id, span; kind }
+    Variant { id, span; attrs, name, data }
+    Enum { id, span; variants }
+    Param { id, span; name }
+    ParamGroup { id, span; attrs, vis, params, ty }
+    Record { id, span; fields }
+    TyDef { id, span; vis, name, ty }
+    Binding { id, span; mutable, name }
+    Pattern { id, span; kind }
+    Let { id, span; pat, ty, init }
+    Stmt { id, span; kind }
+    Block { id, span; stmts, expr }
+    IfBranch { id, span; cond, body }
+    If { id, span; branches, else_branch }
+    MatchArm { id, span; pat, body }
+    Match { id, span; scrutinee, arms }
+    WhenArm { id, span; cond, body }
+    When { id, span; arms, else_branch }
+    For { id, span; pat, iter, body }
+    Lambda { id, span; params, body }
+    Expr { id, span; kind }
+    Receiver { id, span; kind }
+    FnDef { id, span; vis, name, receiver, params, ret, body }
+    ConstDef { id, span; vis, name, ty, value }
+    Method { id, span; attrs, fn_def }
+    Impl { id, span; target, methods }
+    Module { id, span; name, attrs, items }
+    Import { id, span; path, kind }
+    Item { id, span; attrs, kind }
+    SourceFile { id, span; attrs, items }
+}
+
+impl StructuralEq for Token {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind() && self.symbol() == other.symbol()
+    }
+}
+
+impl StructuralEq for TokenTree {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TokenTree::Token(a), TokenTree::Token(b)) => a.structurally_eq(b),
+            (TokenTree::Delimited(a), TokenTree::Delimited(b)) => a.structurally_eq(b),
+            _ => false,
+        }
+    }
+}
+
+impl StructuralEq for Visibility {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        self.is_public() == other.is_public()
+    }
+}
+
+impl StructuralEq for TyKind {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        use TyKind::*;
+
+        match (self, other) {
+            (Path(a), Path(b)) => a.structurally_eq(b),
+            (Tuple(a), Tuple(b)) => a.structurally_eq(b),
+            (Array(a), Array(b)) => a.structurally_eq(b),
+            (Fn(a), Fn(b)) => a.structurally_eq(b),
+            (RawPtr(a), RawPtr(b)) => a.structurally_eq(b),
+            (
+                Ref { mutable, ty },
+                Ref {
+                    mutable: mutable2,
+                    ty: ty2,
+                },
+            ) => mutable == mutable2 && ty.structurally_eq(ty2),
+            (Never, Never) => true,
+            _ => false,
+        }
+    }
+}
+
+impl StructuralEq for TyDefKind {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        use TyDefKind::*;
+
+        match (self, other) {
+            (Alias(a), Alias(b)) => a.structurally_eq(b),
+            (Record(a), Record(b)) => a.structurally_eq(b),
+            (Enum(a), Enum(b)) => a.structurally_eq(b),
+            _ => false,
+        }
+    }
+}
+
+impl StructuralEq for PatternKind {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        use PatternKind::*;
+
+        match (self, other) {
+            (Wild, Wild) => true,
+            (Binding(a), Binding(b)) => a.structurally_eq(b),
+            (Lit(a), Lit(b)) | (Neg(a), Neg(b)) => a.structurally_eq(b),
+            (Tuple(a), Tuple(b)) | (Array(a), Array(b)) => a.structurally_eq(b),
+            (Ctor(path, pats), Ctor(path2, pats2)) => {
+                path.structurally_eq(path2) && pats.structurally_eq(pats2)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl StructuralEq for StmtKind {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (StmtKind::Let(a), StmtKind::Let(b)) => a.structurally_eq(b),
+            (StmtKind::Expr(a), StmtKind::Expr(b)) => a.structurally_eq(b),
+            _ => false,
+        }
+    }
+}
+
+impl StructuralEq for ExprKind {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        use ExprKind::*;
+
+        match (self, other) {
+            (Lit(a), Lit(b)) => a.structurally_eq(b),
+            (Path(a), Path(b)) => a.structurally_eq(b),
+            (Tuple(a), Tuple(b)) => a.structurally_eq(b),
+            (Call(f, args), Call(f2, args2)) => {
+                f.structurally_eq(f2) && args.structurally_eq(args2)
+            }
+            (Unary(op, e), Unary(op2, e2)) => op == op2 && e.structurally_eq(e2),
+            (Binary(op, lhs, rhs), Binary(op2, lhs2, rhs2)) => {
+                op == op2 && lhs.structurally_eq(lhs2) && rhs.structurally_eq(rhs2)
+            }
+            (Assign(place, value), Assign(place2, value2)) => {
+                place.structurally_eq(place2) && value.structurally_eq(value2)
+            }
+            (Is(e, pat), Is(e2, pat2)) => e.structurally_eq(e2) && pat.structurally_eq(pat2),
+            (Block(a), Block(b)) | (Loop(a), Loop(b)) => a.structurally_eq(b),
+            (If(a), If(b)) => a.structurally_eq(b),
+            (Match(a), Match(b)) => a.structurally_eq(b),
+            (When(a), When(b)) => a.structurally_eq(b),
+            (For(a), For(b)) => a.structurally_eq(b),
+            (Break, Break) | (Continue, Continue) => true,
+            (Return(a), Return(b)) => a.structurally_eq(b),
+            (Lambda(a), Lambda(b)) => a.structurally_eq(b),
+            _ => false,
+        }
+    }
+}
+
+impl StructuralEq for ImportKind {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ImportKind::Single(a), ImportKind::Single(b)) => a.structurally_eq(b),
+            (ImportKind::Glob, ImportKind::Glob) => true,
+            _ => false,
+        }
+    }
+}
+
+impl StructuralEq for ItemKind {
+    fn structurally_eq(&self, other: &Self) -> bool {
+        use ItemKind::*;
+
+        match (self, other) {
+            (TyDef(a), TyDef(b)) => a.structurally_eq(b),
+            (FnDef(a), FnDef(b)) => a.structurally_eq(b),
+            (ConstDef(a), ConstDef(b)) => a.structurally_eq(b),
+            (Impl(a), Impl(b)) => a.structurally_eq(b),
+            (Module(a), Module(b)) => a.structurally_eq(b),
+            (Import(a), Import(b)) => a.structurally_eq(b),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use super::*;
+
+/// Traverses the AST by shared reference. Every method defaults to the
+/// matching `walk_*` function, which visits the children of the node, so
+/// passes only override the nodes they care about and call `walk_*` to keep
+/// recursing.
+pub trait Visitor<'ast>: Sized {
+    fn visit_source_file(&mut self, file: &'ast SourceFile) {
+        walk_source_file(self, file)
+    }
+
+    fn visit_item(&mut self, item: &'ast Item) {
+        walk_item(self, item)
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        walk_attribute(self, attr)
+    }
+
+    fn visit_delim_args(&mut self, _args: &'ast DelimArgs) {}
+
+    fn visit_visibility(&mut self, _vis: &'ast Visibility) {}
+
+    fn visit_ident(&mut self, _ident: &'ast Ident) {}
+
+    fn visit_path(&mut self, path: &'ast Path) {
+        walk_path(self, path)
+    }
+
+    fn visit_lit(&mut self, _lit: &'ast Lit) {}
+
+    fn visit_ty_def(&mut self, ty_def: &'ast TyDef) {
+        walk_ty_def(self, ty_def)
+    }
+
+    fn visit_ty_def_kind(&mut self, kind: &'ast TyDefKind) {
+        walk_ty_def_kind(self, kind)
+    }
+
+    fn visit_record(&mut self, record: &'ast Record) {
+        walk_record(self, record)
+    }
+
+    fn visit_enum(&mut self, enum_: &'ast Enum) {
+        walk_enum(self, enum_)
+    }
+
+    fn visit_variant(&mut self, variant: &'ast Variant) {
+        walk_variant(self, variant)
+    }
+
+    fn visit_param_group(&mut self, group: &'ast ParamGroup) {
+        walk_param_group(self, group)
+    }
+
+    fn visit_param(&mut self, param: &'ast Param) {
+        walk_param(self, param)
+    }
+
+    fn visit_ty(&mut self, ty: &'ast Ty) {
+        walk_ty(self, ty)
+    }
+
+    fn visit_tuple(&mut self, tuple: &'ast Tuple) {
+        walk_tuple(self, tuple)
+    }
+
+    fn visit_array(&mut self, array: &'ast Array) {
+        walk_array(self, array)
+    }
+
+    fn visit_fn_ty(&mut self, fn_ty: &'ast FnTy) {
+        walk_fn_ty(self, fn_ty)
+    }
+
+    fn visit_pattern(&mut self, pat: &'ast Pattern) {
+        walk_pattern(self, pat)
+    }
+
+    fn visit_binding(&mut self, binding: &'ast Binding) {
+        walk_binding(self, binding)
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_let(&mut self, let_: &'ast Let) {
+        walk_let(self, let_)
+    }
+
+    fn visit_if(&mut self, if_: &'ast If) {
+        walk_if(self, if_)
+    }
+
+    fn visit_if_branch(&mut self, branch: &'ast IfBranch) {
+        walk_if_branch(self, branch)
+    }
+
+    fn visit_match(&mut self, match_: &'ast Match) {
+        walk_match(self, match_)
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_when(&mut self, when: &'ast When) {
+        walk_when(self, when)
+    }
+
+    fn visit_when_arm(&mut self, arm: &'ast WhenArm) {
+        walk_when_arm(self, arm)
+    }
+
+    fn visit_for(&mut self, for_: &'ast For) {
+        walk_for(self, for_)
+    }
+
+    fn visit_lambda(&mut self, lambda: &'ast Lambda) {
+        walk_lambda(self, lambda)
+    }
+
+    fn visit_fn_def(&mut self, fn_def: &'ast FnDef) {
+        walk_fn_def(self, fn_def)
+    }
+
+    fn visit_receiver(&mut self, _receiver: &'ast Receiver) {}
+
+    fn visit_const_def(&mut self, const_def: &'ast ConstDef) {
+        walk_const_def(self, const_def)
+    }
+
+    fn visit_impl(&mut self, impl_: &'ast Impl) {
+        walk_impl(self, impl_)
+    }
+
+    fn visit_method(&mut self, method: &'ast Method) {
+        walk_method(self, method)
+    }
+
+    fn visit_module(&mut self, module: &'ast Module) {
+        walk_module(self, module)
+    }
+
+    fn visit_import(&mut self, import: &'ast Import) {
+        walk_import(self, import)
+    }
+}
+
+pub fn walk_source_file<'a, V: Visitor<'a>>(v: &mut V, file: &'a SourceFile) {
+    for attr in &file.attrs {
+        v.visit_attribute(attr);
+    }
+    for item in &file.items {
+        v.visit_item(item);
+    }
+}
+
+pub fn walk_item<'a, V: Visitor<'a>>(v: &mut V, item: &'a Item) {
+    for attr in &item.attrs {
+        v.visit_attribute(attr);
+    }
+    match &item.kind {
+        ItemKind::TyDef(ty_def) => v.visit_ty_def(ty_def),
+        ItemKind::FnDef(fn_def) => v.visit_fn_def(fn_def),
+        ItemKind::ConstDef(const_def) => v.visit_const_def(const_def),
+        ItemKind::Impl(impl_) => v.visit_impl(impl_),
+        ItemKind::Module(module) => v.visit_module(module),
+        ItemKind::Import(import) => v.visit_import(import),
+    }
+}
+
+pub fn walk_attribute<'a, V: Visitor<'a>>(v: &mut V, attr: &'a Attribute) {
+    v.visit_path(&attr.path);
+    if let Some(args) = &attr.args {
+        v.visit_delim_args(args);
+    }
+}
+
+pub fn walk_path<'a, V: Visitor<'a>>(v: &mut V, path: &'a Path) {
+    for segment in &path.segments {
+        v.visit_ident(segment);
+    }
+}
+
+pub fn walk_ty_def<'a, V: Visitor<'a>>(v: &mut V, ty_def: &'a TyDef) {
+    v.visit_visibility(&ty_def.vis);
+    v.visit_ident(&ty_def.name);
+    v.visit_ty_def_kind(&ty_def.ty);
+}
+
+pub fn walk_ty_def_kind<'a, V: Visitor<'a>>(v: &mut V, kind: &'a TyDefKind) {
+    match kind {
+        TyDefKind::Alias(ty) => v.visit_ty(ty),
+        TyDefKind::Record(record) => v.visit_record(record),
+        TyDefKind::Enum(enum_) => v.visit_enum(enum_),
+    }
+}
+
+pub fn walk_record<'a, V: Visitor<'a>>(v: &mut V, record: &'a Record) {
+    for group in &record.fields {
+        v.visit_param_group(group);
+    }
+}
+
+pub fn walk_enum<'a, V: Visitor<'a>>(v: &mut V, enum_: &'a Enum) {
+    for variant in &enum_.variants {
+        v.visit_variant(variant);
+    }
+}
+
+pub fn walk_variant<'a, V: Visitor<'a>>(v: &mut V, variant: &'a Variant) {
+    for attr in &variant.attrs {
+        v.visit_attribute(attr);
+    }
+    v.visit_ident(&variant.name);
+    for ty in &variant.data {
+        v.visit_ty(ty);
+    }
+}
+
+pub fn walk_param_group<'a, V: Visitor<'a>>(v: &mut V, group: &'a ParamGroup) {
+    for attr in &group.attrs {
+        v.visit_attribute(attr);
+    }
+    v.visit_visibility(&group.vis);
+    for param in &group.params {
+        v.visit_param(param);
+    }
+    v.visit_ty(&group.ty);
+}
+
+pub fn walk_param<'a, V: Visitor<'a>>(v: &mut V, param: &'a Param) {
+    v.visit_ident(&param.name);
+}
+
+pub fn walk_ty<'a, V: Visitor<'a>>(v: &mut V, ty: &'a Ty) {
+    match &ty.kind {
+        TyKind::Path(path) => v.visit_path(path),
+        TyKind::Tuple(tuple) => v.visit_tuple(tuple),
+        TyKind::Array(array) => v.visit_array(array),
+        TyKind::Fn(fn_ty) => v.visit_fn_ty(fn_ty),
+        TyKind::RawPtr(ty) | TyKind::Ref { ty, .. } => v.visit_ty(ty),
+        TyKind::Never => {}
+    }
+}
+
+pub fn walk_tuple<'a, V: Visitor<'a>>(v: &mut V, tuple: &'a Tuple) {
+    for ty in &tuple.types {
+        v.visit_ty(ty);
+    }
+}
+
+pub fn walk_array<'a, V: Visitor<'a>>(v: &mut V, array: &'a Array) {
+    v.visit_ty(&array.ty);
+    v.visit_expr(&array.len);
+}
+
+pub fn walk_fn_ty<'a, V: Visitor<'a>>(v: &mut V, fn_ty: &'a FnTy) {
+    v.visit_ty(&fn_ty.param);
+    v.visit_ty(&fn_ty.ret);
+}
+
+pub fn walk_pattern<'a, V: Visitor<'a>>(v: &mut V, pat: &'a Pattern) {
+    match &pat.kind {
+        PatternKind::Wild => {}
+        PatternKind::Binding(binding) => v.visit_binding(binding),
+        PatternKind::Lit(lit) | PatternKind::Neg(lit) => v.visit_lit(lit),
+        PatternKind::Tuple(pats) | PatternKind::Array(pats) => {
+            for pat in pats {
+                v.visit_pattern(pat);
+            }
+        }
+        PatternKind::Ctor(path, args) => {
+            v.visit_path(path);
+            for arg in args {
+                v.visit_pattern(arg);
+            }
+        }
+    }
+}
+
+pub fn walk_binding<'a, V: Visitor<'a>>(v: &mut V, binding: &'a Binding) {
+    v.visit_ident(&binding.name);
+}
+
+pub fn walk_expr<'a, V: Visitor<'a>>(v: &mut V, expr: &'a Expr) {
+    match &expr.kind {
+        ExprKind::Lit(lit) => v.visit_lit(lit),
+        ExprKind::Path(path) => v.visit_path(path),
+        ExprKind::Tuple(exprs) => {
+            for expr in exprs {
+                v.visit_expr(expr);
+            }
+        }
+        ExprKind::Call(callee, args) => {
+            v.visit_expr(callee);
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        ExprKind::Unary(_, operand) => v.visit_expr(operand),
+        ExprKind::Binary(_, lhs, rhs) | ExprKind::Assign(lhs, rhs) => {
+            v.visit_expr(lhs);
+            v.visit_expr(rhs);
+        }
+        ExprKind::Is(expr, pat) => {
+            v.visit_expr(expr);
+            v.visit_pattern(pat);
+        }
+        ExprKind::Block(block) | ExprKind::Loop(block) => v.visit_block(block),
+        ExprKind::If(if_) => v.visit_if(if_),
+        ExprKind::Match(match_) => v.visit_match(match_),
+        ExprKind::When(when) => v.visit_when(when),
+        ExprKind::For(for_) => v.visit_for(for_),
+        ExprKind::Break | ExprKind::Continue => {}
+        ExprKind::Return(value) => {
+            if let Some(value) = value {
+                v.visit_expr(value);
+            }
+        }
+        ExprKind::Lambda(lambda) => v.visit_lambda(lambda),
+    }
+}
+
+pub fn walk_block<'a, V: Visitor<'a>>(v: &mut V, block: &'a Block) {
+    for stmt in &block.stmts {
+        v.visit_stmt(stmt);
+    }
+    if let Some(expr) = &block.expr {
+        v.visit_expr(expr);
+    }
+}
+
+pub fn walk_stmt<'a, V: Visitor<'a>>(v: &mut V, stmt: &'a Stmt) {
+    match &stmt.kind {
+        StmtKind::Let(let_) => v.visit_let(let_),
+        StmtKind::Expr(expr) => v.visit_expr(expr),
+    }
+}
+
+pub fn walk_let<'a, V: Visitor<'a>>(v: &mut V, let_: &'a Let) {
+    v.visit_pattern(&let_.pat);
+    if let Some(ty) = &let_.ty {
+        v.visit_ty(ty);
+    }
+    v.visit_expr(&let_.init);
+}
+
+pub fn walk_if<'a, V: Visitor<'a>>(v: &mut V, if_: &'a If) {
+    for branch in &if_.branches {
+        v.visit_if_branch(branch);
+    }
+    if let Some(block) = &if_.else_branch {
+        v.visit_block(block);
+    }
+}
+
+pub fn walk_if_branch<'a, V: Visitor<'a>>(v: &mut V, branch: &'a IfBranch) {
+    v.visit_expr(&branch.cond);
+    v.visit_block(&branch.body);
+}
+
+pub fn walk_match<'a, V: Visitor<'a>>(v: &mut V, match_: &'a Match) {
+    v.visit_expr(&match_.scrutinee);
+    for arm in &match_.arms {
+        v.visit_match_arm(arm);
+    }
+}
+
+pub fn walk_match_arm<'a, V: Visitor<'a>>(v: &mut V, arm: &'a MatchArm) {
+    v.visit_pattern(&arm.pat);
+    v.visit_expr(&arm.body);
+}
+
+pub fn walk_when<'a, V: Visitor<'a>>(v: &mut V, when: &'a When) {
+    for arm in &when.arms {
+        v.visit_when_arm(arm);
+    }
+    if let Some(block) = &when.else_branch {
+        v.visit_block(block);
+    }
+}
+
+pub fn walk_when_arm<'a, V: Visitor<'a>>(v: &mut V, arm: &'a WhenArm) {
+    v.visit_expr(&arm.cond);
+    v.visit_expr(&arm.body);
+}
+
+pub fn walk_for<'a, V: Visitor<'a>>(v: &mut V, for_: &'a For) {
+    v.visit_pattern(&for_.pat);
+    v.visit_expr(&for_.iter);
+    v.visit_block(&for_.body);
+}
+
+pub fn walk_lambda<'a, V: Visitor<'a>>(v: &mut V, lambda: &'a Lambda) {
+    for group in &lambda.params {
+        v.visit_param_group(group);
+    }
+    v.visit_expr(&lambda.body);
+}
+
+pub fn walk_fn_def<'a, V: Visitor<'a>>(v: &mut V, fn_def: &'a FnDef) {
+    v.visit_visibility(&fn_def.vis);
+    v.visit_ident(&fn_def.name);
+    if let Some(receiver) = &fn_def.receiver {
+        v.visit_receiver(receiver);
+    }
+    for group in &fn_def.params {
+        v.visit_param_group(group);
+    }
+    if let Some(ret) = &fn_def.ret {
+        v.visit_ty(ret);
+    }
+    v.visit_expr(&fn_def.body);
+}
+
+pub fn walk_const_def<'a, V: Visitor<'a>>(v: &mut V, const_def: &'a ConstDef) {
+    v.visit_visibility(&const_def.vis);
+    v.visit_ident(&const_def.name);
+    if let Some(ty) = &const_def.ty {
+        v.visit_ty(ty);
+    }
+    v.visit_expr(&const_def.value);
+}
+
+pub fn walk_impl<'a, V: Visitor<'a>>(v: &mut V, impl_: &'a Impl) {
+    v.visit_path(&impl_.target);
+    for method in &impl_.methods {
+        v.visit_method(method);
+    }
+}
+
+pub fn walk_method<'a, V: Visitor<'a>>(v: &mut V, method: &'a Method) {
+    for attr in &method.attrs {
+        v.visit_attribute(attr);
+    }
+    v.visit_fn_def(&method.fn_def);
+}
+
+pub fn walk_module<'a, V: Visitor<'a>>(v: &mut V, module: &'a Module) {
+    v.visit_ident(&module.name);
+    for attr in &module.attrs {
+        v.visit_attribute(attr);
+    }
+    if let Some(items) = &module.items {
+        for item in items {
+            v.visit_item(item);
+        }
+    }
+}
+
+pub fn walk_import<'a, V: Visitor<'a>>(v: &mut V, import: &'a Import) {
+    v.visit_path(&import.path);
+    if let ImportKind::Single(Some(alias)) = &import.kind {
+        v.visit_ident(alias);
+    }
+}
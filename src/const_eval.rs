@@ -0,0 +1,203 @@
+//! Evaluates array lengths at compile time. Lengths are integer expressions
+//! made of literals, arithmetic and constants, which are found through
+//! [name resolution](crate::resolve).
+
+use crate::{
+    ast::{
+        visit::{self, Visitor},
+        Array, BinOp, ConstDef, Expr, ExprKind, Lit, LitKind, NodeId, NodeMap, SourceFile, Ty,
+        TyKind, UnOp,
+    },
+    interner,
+    resolve::{Res, Resolutions},
+    span::Span,
+};
+
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The expression can't be evaluated at compile time.
+    NotConstant(Span),
+    /// The expression refers to a constant that isn't defined.
+    UnknownConstant(Span),
+    /// The constant is defined in terms of itself.
+    Cycle(Span),
+    /// The value doesn't fit in its type, or in the evaluation.
+    Overflow(Span),
+    DivisionByZero(Span),
+    NegativeLength(Span),
+    /// The constant at the span has a type that isn't an integer type.
+    NotInteger(Span),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The lengths of the array types of a file.
+pub struct ArrayLens {
+    /// The length of each array type, by the id of its [`Array`] node. Those
+    /// whose length has an error are missing.
+    pub lens: NodeMap<u64>,
+    pub errors: Vec<Error>,
+}
+
+/// Evaluates the length of every array type of a file, from its resolved
+/// paths.
+pub fn array_lens(file: &SourceFile, resolutions: &Resolutions) -> ArrayLens {
+    let mut checker = LenChecker {
+        eval: ConstEval::new(file, resolutions),
+        lens: NodeMap::new(),
+        errors: Vec::new(),
+    };
+    checker.visit_source_file(file);
+    ArrayLens {
+        lens: checker.lens,
+        errors: checker.errors,
+    }
+}
+
+/// Evaluates integer constant expressions, such as array lengths, in terms of
+/// the constants defined anywhere in a file.
+pub struct ConstEval<'ast> {
+    resolutions: &'ast Resolutions,
+    consts: HashMap<NodeId, &'ast ConstDef>,
+    values: HashMap<NodeId, i128>,
+    in_progress: HashSet<NodeId>,
+}
+
+impl<'ast> ConstEval<'ast> {
+    pub fn new(file: &'ast SourceFile, resolutions: &'ast Resolutions) -> Self {
+        let mut consts = Consts(HashMap::new());
+        consts.visit_source_file(file);
+
+        Self {
+            resolutions,
+            consts: consts.0,
+            values: HashMap::new(),
+            in_progress: HashSet::new(),
+        }
+    }
+
+    /// Evaluates the length of an array type.
+    pub fn array_len(&mut self, array: &Array) -> Result<u64> {
+        let len = self.eval(&array.len)?;
+        if len < 0 {
+            return Err(Error::NegativeLength(array.len.span));
+        }
+        u64::try_from(len).map_err(|_| Error::Overflow(array.len.span))
+    }
+
+    pub fn eval(&mut self, expr: &Expr) -> Result<i128> {
+        match &expr.kind {
+            ExprKind::Lit(Lit {
+                kind: LitKind::Int { value, .. },
+                ..
+            }) => i128::try_from(*value).map_err(|_| Error::Overflow(expr.span)),
+            ExprKind::Path(path) => match self.resolutions.paths.get(path.id) {
+                Some(resolution) if resolution.len < path.segments.len() => {
+                    Err(Error::NotConstant(expr.span))
+                }
+                Some(resolution) => match resolution.res {
+                    Res::Const(id) => self.eval_const(id, expr.span),
+                    _ => Err(Error::NotConstant(expr.span)),
+                },
+                None => Err(Error::UnknownConstant(expr.span)),
+            },
+            ExprKind::Unary(UnOp::Neg, e) => self
+                .eval(e)?
+                .checked_neg()
+                .ok_or(Error::Overflow(expr.span)),
+            ExprKind::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.eval(lhs)?, self.eval(rhs)?);
+                let res = match op {
+                    BinOp::Add => lhs.checked_add(rhs),
+                    BinOp::Sub => lhs.checked_sub(rhs),
+                    BinOp::Mul => lhs.checked_mul(rhs),
+                    BinOp::Div if rhs == 0 => return Err(Error::DivisionByZero(expr.span)),
+                    BinOp::Div => lhs.checked_div(rhs),
+                    _ => return Err(Error::NotConstant(expr.span)),
+                };
+                res.ok_or(Error::Overflow(expr.span))
+            }
+            _ => Err(Error::NotConstant(expr.span)),
+        }
+    }
+
+    fn eval_const(&mut self, id: NodeId, span: Span) -> Result<i128> {
+        if let Some(&value) = self.values.get(&id) {
+            return Ok(value);
+        }
+
+        let def = *self.consts.get(&id).ok_or(Error::UnknownConstant(span))?;
+        if !self.in_progress.insert(id) {
+            return Err(Error::Cycle(span));
+        }
+        let value = self.eval(&def.value);
+        self.in_progress.remove(&id);
+
+        let value = value?;
+        if let Some(ty) = &def.ty {
+            let (min, max) = self.int_range(ty)?;
+            if !(min..=max).contains(&value) {
+                return Err(Error::Overflow(def.value.span));
+            }
+        }
+        self.values.insert(id, value);
+        Ok(value)
+    }
+
+    /// The values of the integer type a constant is declared with.
+    fn int_range(&self, ty: &Ty) -> Result<(i128, i128)> {
+        let name = match &ty.kind {
+            TyKind::Path(path) => match self.resolutions.paths.get(path.id) {
+                Some(resolution) => match resolution.res {
+                    Res::PrimTy(name) => interner::get(name),
+                    _ => return Err(Error::NotInteger(ty.span)),
+                },
+                // the type is unresolved, which has been reported already
+                None => return Ok((i128::MIN, i128::MAX)),
+            },
+            _ => return Err(Error::NotInteger(ty.span)),
+        };
+        Ok(match name {
+            "u8" => (0, u8::MAX.into()),
+            "u16" => (0, u16::MAX.into()),
+            "u32" => (0, u32::MAX.into()),
+            "u64" => (0, u64::MAX.into()),
+            "i8" => (i8::MIN.into(), i8::MAX.into()),
+            "i16" => (i16::MIN.into(), i16::MAX.into()),
+            "i32" => (i32::MIN.into(), i32::MAX.into()),
+            "i64" => (i64::MIN.into(), i64::MAX.into()),
+            _ => return Err(Error::NotInteger(ty.span)),
+        })
+    }
+}
+
+/// Collects the constants of a file, inside modules too.
+struct Consts<'ast>(HashMap<NodeId, &'ast ConstDef>);
+
+impl<'ast> Visitor<'ast> for Consts<'ast> {
+    fn visit_const_def(&mut self, const_def: &'ast ConstDef) {
+        self.0.insert(const_def.id, const_def);
+    }
+}
+
+struct LenChecker<'ast> {
+    eval: ConstEval<'ast>,
+    lens: NodeMap<u64>,
+    errors: Vec<Error>,
+}
+
+impl<'ast> Visitor<'ast> for LenChecker<'ast> {
+    fn visit_array(&mut self, array: &'ast Array) {
+        match self.eval.array_len(array) {
+            Ok(len) => {
+                self.lens.insert(array.id, len);
+            }
+            // a constant used by several arrays is only reported once
+            Err(err) if self.errors.contains(&err) => {}
+            Err(err) => self.errors.push(err),
+        }
+        visit::walk_array(self, array)
+    }
+}
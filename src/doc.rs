@@ -0,0 +1,171 @@
+//! Documents laid out to a line width, after Wadler's "A prettier printer".
+//!
+//! A [`Doc`] describes text along with the places where lines may be broken.
+//! [Groups](Doc::group) are printed on a single line if they fit in what is
+//! left of the line, and otherwise have all their own line breaks taken.
+
+use std::mem;
+
+#[derive(Clone, Debug)]
+pub enum Doc {
+    Nil,
+    /// Text without newlines.
+    Text(String),
+    /// A space, or a newline if the enclosing group is broken.
+    Line,
+    /// Nothing, or a newline if the enclosing group is broken.
+    SoftLine,
+    /// Always a newline, which breaks the enclosing groups.
+    HardLine,
+    /// Breaks the enclosing groups without printing anything.
+    BreakParent,
+    /// Text printed at the end of the current line, before the next newline,
+    /// e.g. a trailing comment.
+    LineSuffix(String),
+    /// Indents the lines started inside by that many more spaces.
+    Nest(usize, Box<Doc>),
+    Group(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// A part of a document left to print, with its indentation and mode.
+type Command<'a> = (usize, Mode, &'a Doc);
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Self {
+        Doc::Text(text.into())
+    }
+
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Self {
+        Doc::Concat(docs.into_iter().collect())
+    }
+
+    /// Puts `sep` between the documents.
+    pub fn join(docs: impl IntoIterator<Item = Doc>, sep: Doc) -> Self {
+        let mut joined = Vec::new();
+        for doc in docs {
+            if !joined.is_empty() {
+                joined.push(sep.clone());
+            }
+            joined.push(doc);
+        }
+        Doc::Concat(joined)
+    }
+
+    pub fn nest(self, indent: usize) -> Self {
+        Doc::Nest(indent, Box::new(self))
+    }
+
+    pub fn group(self) -> Self {
+        Doc::Group(Box::new(self))
+    }
+
+    pub fn append(self, doc: Doc) -> Self {
+        match self {
+            Doc::Nil => doc,
+            Doc::Concat(mut docs) => {
+                docs.push(doc);
+                Doc::Concat(docs)
+            }
+            this => Doc::Concat(vec![this, doc]),
+        }
+    }
+
+    /// Lays the document out to fit in `width` columns where possible.
+    /// Lines don't end with spaces.
+    pub fn render(&self, width: usize) -> String {
+        let mut printer = Printer {
+            out: String::new(),
+            col: 0,
+            suffixes: Vec::new(),
+        };
+        let mut stack: Vec<Command> = vec![(0, Mode::Break, self)];
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil | Doc::BreakParent => {}
+                Doc::Text(text) => printer.text(text),
+                Doc::LineSuffix(text) => printer.suffixes.push(text),
+                Doc::Line if mode == Mode::Flat => printer.text(" "),
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => printer.newline(indent),
+                Doc::Nest(more, doc) => stack.push((indent + more, mode, doc)),
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+                Doc::Group(doc) => {
+                    let left = width as isize - printer.col as isize;
+                    let mode =
+                        if mode == Mode::Flat || fits(left, (indent, Mode::Flat, doc), &stack) {
+                            Mode::Flat
+                        } else {
+                            Mode::Break
+                        };
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+
+        printer.end_line();
+        printer.out
+    }
+}
+
+/// Whether `next` fits in `width` columns when printed flat, along with what
+/// follows it up to the next newline.
+fn fits(mut width: isize, next: Command, rest: &[Command]) -> bool {
+    let mut stack = vec![next];
+    let mut rest = rest.iter().rev();
+
+    while width >= 0 {
+        let Some((indent, mode, doc)) = stack.pop().or_else(|| rest.next().copied()) else {
+            return true;
+        };
+        match doc {
+            Doc::Nil | Doc::LineSuffix(_) => {}
+            Doc::Text(text) => width -= text.chars().count() as isize,
+            Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+            Doc::Line => width -= 1,
+            Doc::SoftLine => {}
+            Doc::HardLine => return mode == Mode::Break,
+            Doc::BreakParent if mode == Mode::Flat => return false,
+            Doc::BreakParent => {}
+            Doc::Nest(more, doc) => stack.push((indent + more, mode, doc)),
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            Doc::Group(doc) => stack.push((indent, mode, doc)),
+        }
+    }
+    false
+}
+
+struct Printer<'a> {
+    out: String,
+    col: usize,
+    suffixes: Vec<&'a str>,
+}
+
+impl Printer<'_> {
+    fn text(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.col += text.chars().count();
+    }
+
+    /// Ends the line with the pending suffixes, without trailing spaces.
+    fn end_line(&mut self) {
+        self.out.truncate(self.out.trim_end_matches(' ').len());
+        for suffix in mem::take(&mut self.suffixes) {
+            self.out.push_str(suffix);
+        }
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.end_line();
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat_n(' ', indent));
+        self.col = indent;
+    }
+}
@@ -1,12 +1,19 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Symbol(u32);
 
+/// Strings are leaked, so that they can be handed out for the rest of the
+/// program while the interner keeps growing. Only names and literals are
+/// interned, whose set grows slowly as a file is edited; the text of syntax
+/// trees, trivia included, is owned by the trees.
 #[derive(Default, Clone, Debug)]
 pub struct Interner {
-    syms: HashMap<String, Symbol>,
-    strings: Vec<String>,
+    syms: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
 }
 
 impl Interner {
@@ -20,37 +27,36 @@ impl Interner {
     pub fn insert(&mut self, string: impl Into<String>) -> Symbol {
         let string = string.into();
         if let Some(&sym) = self.syms.get(string.as_str()) {
-            return sym;
+            sym
         } else {
             let sym = Symbol(u32::try_from(self.strings.len()).unwrap());
-            self.syms.insert(string.clone(), sym);
+            let string: &'static str = Box::leak(string.into_boxed_str());
+            self.syms.insert(string, sym);
             self.strings.push(string);
-            return sym;
+            sym
         }
     }
 
-    pub fn get(&self, sym: Symbol) -> &str {
-        self.strings[usize::try_from(sym.0).unwrap()].as_str()
+    pub fn get(&self, sym: Symbol) -> &'static str {
+        self.strings[usize::try_from(sym.0).unwrap()]
     }
 }
 
-pub static mut INTERNER: Option<Interner> = None;
+/// The interner of [`intern`] and [`get`], shared by every thread so that
+/// symbols mean the same everywhere.
+static INTERNER: OnceLock<Mutex<Interner>> = OnceLock::new();
+
+fn interner() -> MutexGuard<'static, Interner> {
+    let interner = INTERNER.get_or_init(|| Mutex::new(Interner::new()));
+    // the interner is left consistent by a panic, since it can only happen
+    // before it changes
+    interner.lock().unwrap_or_else(|err| err.into_inner())
+}
 
 pub fn intern(string: impl Into<String>) -> Symbol {
-    match unsafe { &mut INTERNER } {
-        Some(interner) => interner.insert(string),
-        None => {
-            unsafe {
-                INTERNER = Some(Interner::new());
-            }
-            intern(string)
-        }
-    }
+    interner().insert(string)
 }
 
 pub fn get(sym: Symbol) -> &'static str {
-    match unsafe { &INTERNER } {
-        Some(interner) => interner.get(sym),
-        None => panic!("interner not initialized"),
-    }
+    interner().get(sym)
 }
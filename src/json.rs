@@ -0,0 +1,339 @@
+//! A minimal JSON value, with a printer and a parser.
+
+use std::fmt::{self, Display, Write};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    /// Numbers keep their text, so that integers wider than an `f64` survive.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    /// Fields keep their order.
+    Object(Vec<(String, Json)>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// Invalid JSON at the byte offset.
+    Syntax(usize),
+    MissingField(String),
+    /// A value doesn't have the expected type, e.g. a string instead of a
+    /// number.
+    InvalidValue(&'static str),
+    /// An object describes a different node than expected.
+    UnexpectedNode(String),
+    UnknownVariant(String),
+    InvalidToken(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json> {
+        let mut parser = Parser { text, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(Error::Syntax(parser.pos));
+        }
+        Ok(value)
+    }
+
+    pub fn number(n: impl Display) -> Json {
+        Json::Number(n.to_string())
+    }
+
+    /// Floats are printed so that they parse back to the same value. JSON has
+    /// no infinity, so it is written as a number too large for an `f64`.
+    pub fn float(f: f64) -> Json {
+        if f.is_infinite() {
+            Json::Number(if f > 0.0 { "1e999" } else { "-1e999" }.to_string())
+        } else {
+            Json::Number(format!("{f:?}"))
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Gets the field of an object, which must be there.
+    pub fn field(&self, key: &str) -> Result<&Json> {
+        self.get(key)
+            .ok_or_else(|| Error::MissingField(key.to_string()))
+    }
+
+    pub fn as_bool(&self) -> Result<bool> {
+        match self {
+            Json::Bool(b) => Ok(*b),
+            _ => Err(Error::InvalidValue("a boolean")),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&str> {
+        match self {
+            Json::String(s) => Ok(s),
+            _ => Err(Error::InvalidValue("a string")),
+        }
+    }
+
+    pub fn as_array(&self) -> Result<&[Json]> {
+        match self {
+            Json::Array(values) => Ok(values),
+            _ => Err(Error::InvalidValue("an array")),
+        }
+    }
+
+    pub fn as_u32(&self) -> Result<u32> {
+        self.as_number()?
+            .parse()
+            .map_err(|_| Error::InvalidValue("a 32-bit unsigned integer"))
+    }
+
+    pub fn as_u128(&self) -> Result<u128> {
+        self.as_number()?
+            .parse()
+            .map_err(|_| Error::InvalidValue("an unsigned integer"))
+    }
+
+    pub fn as_f64(&self) -> Result<f64> {
+        self.as_number()?
+            .parse()
+            .map_err(|_| Error::InvalidValue("a number"))
+    }
+
+    fn as_number(&self) -> Result<&str> {
+        match self {
+            Json::Number(n) => Ok(n),
+            _ => Err(Error::InvalidValue("a number")),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: Option<usize>) -> fmt::Result {
+        fn newline(f: &mut fmt::Formatter<'_>, indent: Option<usize>) -> fmt::Result {
+            match indent {
+                Some(indent) => write!(f, "\n{:1$}", "", indent),
+                None => Ok(()),
+            }
+        }
+
+        let inner = indent.map(|indent| indent + 2);
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Number(n) => write!(f, "{n}"),
+            Json::String(s) => write_str(f, s),
+            Json::Array(values) if values.is_empty() => write!(f, "[]"),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    newline(f, inner)?;
+                    value.write(f, inner)?;
+                }
+                newline(f, indent)?;
+                write!(f, "]")
+            }
+            Json::Object(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    newline(f, inner)?;
+                    write_str(f, key)?;
+                    write!(f, ":")?;
+                    if indent.is_some() {
+                        write!(f, " ")?;
+                    }
+                    value.write(f, inner)?;
+                }
+                newline(f, indent)?;
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Compact by default; `{:#}` indents nested values.
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, f.alternate().then_some(0))
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let found = self.text[self.pos..].starts_with(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, s: &str) -> Result<()> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(Error::Syntax(self.pos))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect("null").map(|_| Json::Null),
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'-' | b'0'..=b'9') => Ok(self.number()),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                self.skip_whitespace();
+                if !self.eat("]") {
+                    loop {
+                        values.push(self.value()?);
+                        self.skip_whitespace();
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Json::Array(values))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if !self.eat("}") {
+                    loop {
+                        self.skip_whitespace();
+                        if self.peek() != Some(b'"') {
+                            return Err(Error::Syntax(self.pos));
+                        }
+                        let key = self.string()?;
+                        self.skip_whitespace();
+                        self.expect(":")?;
+                        fields.push((key, self.value()?));
+                        self.skip_whitespace();
+                        if self.eat("}") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                Ok(Json::Object(fields))
+            }
+            _ => Err(Error::Syntax(self.pos)),
+        }
+    }
+
+    /// Numbers are checked when they are used, by `Json::as_f64` and the
+    /// like.
+    fn number(&mut self) -> Json {
+        let start = self.pos;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        Json::Number(self.text[start..self.pos].to_string())
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.text[self.pos..].chars().next() else {
+                return Err(Error::Syntax(self.pos));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escape = self.pos - 1;
+                    let c = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.pos += 1;
+                            let hi = self.hex4()?;
+                            let code = if (0xd800..0xdc00).contains(&hi) && self.eat("\\u") {
+                                let lo = self.hex4()?;
+                                if !(0xdc00..0xe000).contains(&lo) {
+                                    return Err(Error::Syntax(escape));
+                                }
+                                0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00)
+                            } else {
+                                hi
+                            };
+                            s.push(char::from_u32(code).ok_or(Error::Syntax(escape))?);
+                            continue;
+                        }
+                        _ => return Err(Error::Syntax(escape)),
+                    };
+                    self.pos += 1;
+                    s.push(c);
+                }
+                // unlike C0 controls, DEL and C1 controls may appear unescaped
+                c if c < '\u{20}' => return Err(Error::Syntax(self.pos - 1)),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or(Error::Syntax(self.pos))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap())
+    }
+}
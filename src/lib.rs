@@ -1,6 +1,12 @@
 pub mod ast;
+pub mod const_eval;
+pub mod doc;
 pub mod interner;
+pub mod json;
 pub mod lexer;
+pub mod load;
 pub mod parser;
+pub mod resolve;
 pub mod span;
+pub mod syntax;
 pub mod token;
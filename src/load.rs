@@ -0,0 +1,193 @@
+//! Loads the files of a program. Each file is a module named after the file,
+//! and `module A.` makes the module of file `A` a submodule of the one
+//! declaring it. Module files are looked for next to the root file, with its
+//! extension, e.g. `module A.` loads `src/A.l1` for a root `src/main.l1`.
+//!
+//! The files are parsed into a single tree, where out-of-line modules hold
+//! the items of their file. They are laid one after the other in a single
+//! range of offsets, and their node ids follow each other, so that spans and
+//! ids stay unique. [`Program::file_at`] finds the file of an offset.
+
+use crate::{
+    ast::{Item, ItemKind, Module, NodeId, SourceFile},
+    interner,
+    lexer::Scanner,
+    parser::{self, Parse},
+    span::Span,
+};
+
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Debug)]
+pub enum Error {
+    /// The file of the module declared at the span doesn't exist.
+    Missing(Span, PathBuf),
+    /// The file of the module declared at the span can't be read.
+    Read(Span, PathBuf, io::ErrorKind),
+    /// The file doesn't parse. The spans of the error are offsets among all
+    /// the files.
+    Parse(PathBuf, parser::Error),
+    /// The file of the module declared at the span is already the file of
+    /// another module.
+    Duplicate(Span),
+    /// The module declared at the span is the file declaring it, or the file
+    /// of a module enclosing it.
+    Cycle(Span),
+}
+
+#[derive(Clone, Debug)]
+pub struct File {
+    pub path: PathBuf,
+    pub src: String,
+    /// The offset at which the file starts.
+    pub offset: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Program {
+    /// The root file, whose out-of-line modules hold the items of their file
+    /// once it's loaded.
+    pub file: SourceFile,
+    /// The files loaded, the root first.
+    pub files: Vec<File>,
+    pub errors: Vec<Error>,
+}
+
+impl Program {
+    /// The file an offset lies in.
+    pub fn file_at(&self, offset: u32) -> &File {
+        let after = self.files.partition_point(|file| file.offset <= offset);
+        &self.files[after.saturating_sub(1)]
+    }
+}
+
+/// Loads the program whose root file is at `root`. Only failing to read the
+/// root file is fatal, other errors are in [`Program::errors`].
+pub fn load(root: &Path) -> io::Result<Program> {
+    let src = fs::read_to_string(root)?;
+    let mut loader = Loader {
+        dir: root.parent().unwrap_or(Path::new("")).to_path_buf(),
+        extension: root.extension().map(|ext| ext.to_os_string()),
+        files: Vec::new(),
+        loaded: HashSet::new(),
+        loading: Vec::new(),
+        next_id: NodeId::new(0),
+        errors: Vec::new(),
+    };
+
+    let key = loader.key(root);
+    loader.loaded.insert(key.clone());
+    loader.loading.push(key);
+    let mut file = match loader.parse(root.to_path_buf(), src) {
+        Some(file) => file,
+        None => SourceFile::new(loader.next_id, Span::default(), Vec::new(), Vec::new()),
+    };
+    loader.modules(&mut file.items);
+
+    Ok(Program {
+        file,
+        files: loader.files,
+        errors: loader.errors,
+    })
+}
+
+struct Loader {
+    /// The directory of the root file, where module files are.
+    dir: PathBuf,
+    extension: Option<OsString>,
+    files: Vec<File>,
+    /// The files loaded or being loaded, to catch duplicates.
+    loaded: HashSet<PathBuf>,
+    /// The files being loaded, innermost last, to catch cycles.
+    loading: Vec<PathBuf>,
+    /// The id the next file's nodes start from.
+    next_id: NodeId,
+    errors: Vec<Error>,
+}
+
+impl Loader {
+    /// Tells files apart, whichever path leads to them.
+    fn key(&self, path: &Path) -> PathBuf {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    /// Parses a file after the ones already loaded.
+    fn parse(&mut self, path: PathBuf, src: String) -> Option<SourceFile> {
+        // files are one offset apart, so that an empty file can't be
+        // mistaken for the next one
+        let offset = self.files.last().map_or(0, |file| {
+            file.offset + u32::try_from(file.src.len()).unwrap() + 1
+        });
+        let mut input = Scanner::with_offset(&src, offset)
+            .stream()
+            .with_first_id(self.next_id);
+        let file = SourceFile::parse_complete(&mut input);
+        self.next_id = input.next_id();
+
+        self.files.push(File {
+            path: path.clone(),
+            src,
+            offset,
+        });
+        file.map_err(|err| self.errors.push(Error::Parse(path, err)))
+            .ok()
+    }
+
+    /// Loads the out-of-line modules among `items`, and inside their inline
+    /// modules.
+    fn modules(&mut self, items: &mut [Item]) {
+        for item in items {
+            if let ItemKind::Module(module) = &mut item.kind {
+                match &mut module.items {
+                    Some(items) => self.modules(items),
+                    None => self.module(module),
+                }
+            }
+        }
+    }
+
+    fn module(&mut self, module: &mut Module) {
+        let mut path = self.dir.join(interner::get(module.name.name));
+        if let Some(extension) = &self.extension {
+            path.set_extension(extension);
+        }
+
+        let key = self.key(&path);
+        if self.loading.contains(&key) {
+            self.errors.push(Error::Cycle(module.span));
+            return;
+        }
+        if !self.loaded.insert(key.clone()) {
+            self.errors.push(Error::Duplicate(module.span));
+            return;
+        }
+        let src = match fs::read_to_string(&path) {
+            Ok(src) => src,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                self.errors.push(Error::Missing(module.span, path));
+                return;
+            }
+            Err(err) => {
+                self.errors.push(Error::Read(module.span, path, err.kind()));
+                return;
+            }
+        };
+
+        let Some(SourceFile {
+            attrs, mut items, ..
+        }) = self.parse(path, src)
+        else {
+            return;
+        };
+        self.loading.push(key);
+        self.modules(&mut items);
+        self.loading.pop();
+        module.attrs = attrs;
+        module.items = Some(items);
+    }
+}
@@ -0,0 +1,132 @@
+//! Parses a melange file and prints its syntax tree, or formats it.
+//!
+//! ```text
+//! melange [--emit sexp|json|dot] <file>
+//! melange fmt [--check] <file>
+//! ```
+//!
+//! `fmt` rewrites the file in place, or with `--check` only fails if the
+//! file isn't formatted.
+
+use melange::{
+    ast::{dot, format, json, SourceFile},
+    parser::parse_str,
+};
+
+use std::{env, fs, process::ExitCode};
+
+const USAGE: &str = "usage: melange [--emit sexp|json|dot] <file>
+       melange fmt [--check] <file>";
+
+#[derive(Clone, Copy)]
+enum Emit {
+    /// The s-expressions of `ast::pretty`
+    Sexp,
+    Json,
+    /// Graphviz
+    Dot,
+}
+
+impl Emit {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sexp" => Some(Emit::Sexp),
+            "json" => Some(Emit::Json),
+            "dot" => Some(Emit::Dot),
+            _ => None,
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{USAGE}");
+    ExitCode::FAILURE
+}
+
+fn read(path: &str) -> Option<String> {
+    fs::read_to_string(path)
+        .inspect_err(|err| eprintln!("{path}: {err}"))
+        .ok()
+}
+
+fn fmt(args: &[String]) -> ExitCode {
+    let (check, path) = match args {
+        [flag, path] if flag == "--check" => (true, path),
+        [path] if !path.starts_with('-') => (false, path),
+        _ => return usage(),
+    };
+
+    let Some(src) = read(path) else {
+        return ExitCode::FAILURE;
+    };
+    let formatted = match format::format_source(&src, format::WIDTH) {
+        Ok(formatted) => formatted,
+        Err(err) => {
+            eprintln!("{path}: {err:?}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if formatted == src {
+        return ExitCode::SUCCESS;
+    }
+    if check {
+        eprintln!("{path} isn't formatted");
+        return ExitCode::FAILURE;
+    }
+    match fs::write(path, formatted) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "fmt") {
+        return fmt(&args[1..]);
+    }
+
+    let mut emit = Emit::Sexp;
+    let mut path = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let format = match arg.strip_prefix("--emit") {
+            Some("") => args.next(),
+            Some(format) => format.strip_prefix('=').map(str::to_string),
+            None if path.is_none() && !arg.starts_with('-') => {
+                path = Some(arg);
+                continue;
+            }
+            None => return usage(),
+        };
+        match format.as_deref().and_then(Emit::from_name) {
+            Some(format) => emit = format,
+            None => return usage(),
+        }
+    }
+    let Some(path) = path else {
+        return usage();
+    };
+
+    let Some(src) = read(&path) else {
+        return ExitCode::FAILURE;
+    };
+    let file: SourceFile = match parse_str(&src) {
+        Ok(file) => file,
+        Err(err) => {
+            eprintln!("{path}: {err:?}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match emit {
+        Emit::Sexp => println!("{file}"),
+        Emit::Json => println!("{:#}", json::to_json(&file, &src)),
+        Emit::Dot => print!("{}", dot::to_dot(&file, &src)),
+    }
+    ExitCode::SUCCESS
+}
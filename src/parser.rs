@@ -1,7 +1,16 @@
 use crate::{
-    ast::{Array, Enum, Ident, Param, Path, Record, Tuple, Ty, TyDef, TyDefKind, TyKind, Variant},
-    lexer::TokenStream,
+    ast::{
+        Array, AttrStyle, Attribute, BinOp, Binding, Block, ConstDef, DelimArgs, Delimiter, Enum,
+        Expr, ExprKind, FloatTy, FnDef, FnTy, For, Ident, If, IfBranch, Impl, Import, ImportKind,
+        IntTy, Item, ItemKind, Lambda, Let, Lit, LitKind, Match, MatchArm, Method, Module, Param,
+        ParamGroup, Path, Pattern, PatternKind, Receiver, ReceiverKind, Record, SourceFile, Stmt,
+        StmtKind, TokenTree, Tuple, Ty, TyDef, TyDefKind, TyKind, UnOp, Variant, Visibility, When,
+        WhenArm, P,
+    },
+    interner,
+    lexer::{Scanner, TokenStream},
     span::Span,
+    syntax::{Checkpoint, NodeKind},
     token::{Kind, Token},
 };
 
@@ -9,6 +18,16 @@ use crate::{
 pub enum Error {
     UnexpectedToken,
     MissingToken,
+    /// The literal doesn't fit in its type, e.g. `300u8`.
+    LiteralOutOfRange(Span),
+    InvalidSuffix(Span),
+    InvalidEscape(Span),
+    /// The text at the span isn't a token, e.g. an unterminated string
+    /// literal.
+    InvalidToken(Span),
+    /// Tokens are left after what was parsed, starting with the one at the
+    /// span.
+    TrailingTokens(Span),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -29,25 +48,78 @@ pub trait Parse: Sized {
 
         Ok(list)
     }
+
+    /// Parses a `Self` which must span the rest of the input.
+    fn parse_complete(input: &mut TokenStream) -> Result<Self> {
+        let node = Self::parse(input)?;
+        match input.peek() {
+            Some(token) if token.kind() == Kind::Error => Err(Error::InvalidToken(token.span())),
+            Some(token) => Err(Error::TrailingTokens(token.span())),
+            None => Ok(node),
+        }
+    }
+}
+
+/// Parses the whole of `src` as a `T`.
+pub fn parse_str<T: Parse>(src: &str) -> Result<T> {
+    T::parse_complete(&mut Scanner::new(src).stream())
 }
 
 trait ParserExtension {
+    fn peek_kind(&mut self) -> Option<Kind>;
+    /// The error for the next token not being the expected one.
+    fn unexpected(&mut self) -> Error;
     fn eat(&mut self, kind: Kind) -> Result<Token>;
     fn eat_if(&mut self, f: impl Fn(Kind) -> bool) -> Result<Token>;
     fn eat_from(&mut self, kinds: &[Kind]) -> Result<Token>;
 }
 
 impl ParserExtension for TokenStream<'_> {
+    fn peek_kind(&mut self) -> Option<Kind> {
+        self.peek().map(|t| t.kind())
+    }
+
+    fn unexpected(&mut self) -> Error {
+        match self.peek() {
+            Some(token) if token.kind() == Kind::Error => Error::InvalidToken(token.span()),
+            _ => Error::UnexpectedToken,
+        }
+    }
+
     fn eat(&mut self, kind: Kind) -> Result<Token> {
-        self.next_if_is(kind).ok_or(Error::UnexpectedToken)
+        self.eat_if(|k| k == kind)
     }
 
     fn eat_if(&mut self, f: impl Fn(Kind) -> bool) -> Result<Token> {
-        self.next_if(|t| f(t.kind())).ok_or(Error::UnexpectedToken)
+        match self.next_if(|t| f(t.kind())) {
+            Some(token) => Ok(token),
+            None => Err(self.unexpected()),
+        }
     }
 
     fn eat_from(&mut self, kinds: &[Kind]) -> Result<Token> {
-        self.eat_if(|tk| kinds.iter().any(|&k| k == tk))
+        self.eat_if(|tk| kinds.contains(&tk))
+    }
+}
+
+impl Parse for Visibility {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        if input.peek_kind() != Some(Kind::Pub) {
+            return Ok(Visibility::Private);
+        }
+
+        let cp = input.checkpoint();
+        let pub_kw = input.eat(Kind::Pub)?.span();
+        input.finish_node(cp, NodeKind::Visibility);
+        Ok(Visibility::Public(pub_kw))
+    }
+}
+
+/// Extends the span of a node to its leading `pub`.
+fn with_vis(vis: Visibility, span: Span) -> Span {
+    match vis {
+        Visibility::Public(pub_kw) => Span::combine(pub_kw, span),
+        Visibility::Private => span,
     }
 }
 
@@ -61,62 +133,219 @@ impl Parse for Ident {
 
 impl Parse for Path {
     fn parse(input: &mut TokenStream) -> Result<Self> {
-        Ident::parse_separated(Kind::Tilde, input).map(|segments| {
-            Path::new(
-                Span::combine(
-                    segments.first().unwrap().span,
-                    segments.last().unwrap().span,
-                ),
-                segments,
-            )
-        })
+        let cp = input.checkpoint();
+        let segments = Ident::parse_separated(Kind::Tilde, input)?;
+        input.finish_node(cp, NodeKind::Path);
+        Ok(Path::new(
+            input.next_id(),
+            Span::combine(
+                segments.first().unwrap().span,
+                segments.last().unwrap().span,
+            ),
+            segments,
+        ))
     }
 }
 
 impl Parse for Tuple {
     fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
         let s1 = input.eat(Kind::LParen)?.span();
-        let res = Ty::parse_separated(Kind::Comma, input)?;
+        // `()` is the unit type
+        let res = if input.peek_kind() == Some(Kind::RParen) {
+            Vec::new()
+        } else {
+            Ty::parse_separated(Kind::Comma, input)?
+        };
         let s2 = input.eat(Kind::RParen)?.span();
-        Ok(Tuple::new(Span::combine(s1, s2), res))
+        input.finish_node(cp, NodeKind::TupleTy);
+        Ok(Tuple::new(input.next_id(), Span::combine(s1, s2), res))
     }
 }
 
 impl Parse for Array {
     fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
         let s1 = input.eat(Kind::LBrack)?.span();
-        let ty = Ty::parse(input).map(Box::new)?;
+        let ty = Ty::parse(input).map(P::new)?;
         input.eat(Kind::Semi)?;
-        let len = input.eat(Kind::IntLit)?;
+        let len = Expr::parse(input).map(P::new)?;
         let s2 = input.eat(Kind::RBrack)?.span();
-        Ok(Array::new(Span::combine(s1, s2), ty, len))
+        input.finish_node(cp, NodeKind::ArrayTy);
+        Ok(Array::new(input.next_id(), Span::combine(s1, s2), ty, len))
     }
 }
 
+/// Parses every type but function types, whose arrows are handled by
+/// `Ty::parse`.
 impl Parse for TyKind {
     fn parse(input: &mut TokenStream) -> Result<Self> {
         match input.peek().ok_or(Error::MissingToken)?.kind() {
             Kind::LParen => Tuple::parse(input).map(TyKind::Tuple),
             Kind::LBrack => Array::parse(input).map(TyKind::Array),
-            _ => Path::parse(input).map(TyKind::Path),
+            Kind::Bang => {
+                let cp = input.checkpoint();
+                input.eat(Kind::Bang)?;
+                input.finish_node(cp, NodeKind::NeverTy);
+                Ok(TyKind::Never)
+            }
+            Kind::Raw => {
+                let cp = input.checkpoint();
+                input.eat(Kind::Raw)?;
+                let ty = parse_ty_operand(input)?;
+                input.finish_node(cp, NodeKind::RawPtrTy);
+                Ok(TyKind::RawPtr(ty))
+            }
+            Kind::Amp => {
+                let cp = input.checkpoint();
+                input.eat(Kind::Amp)?;
+                let mutable = input.eat(Kind::Mut).is_ok();
+                let ty = parse_ty_operand(input)?;
+                input.finish_node(cp, NodeKind::RefTy);
+                Ok(TyKind::Ref { mutable, ty })
+            }
+            _ => {
+                let cp = input.checkpoint();
+                let path = Path::parse(input)?;
+                input.finish_node(cp, NodeKind::PathTy);
+                Ok(TyKind::Path(path))
+            }
         }
     }
 }
 
+/// Parses the type following `raw` or `&`, which binds tighter than arrows:
+/// `&T -> U` is `(&T) -> U`.
+fn parse_ty_operand(input: &mut TokenStream) -> Result<P<Ty>> {
+    let start = input.peek().ok_or(Error::MissingToken)?.span();
+    let kind = TyKind::parse(input)?;
+    let span = match &kind {
+        TyKind::Path(p) => p.span,
+        TyKind::Tuple(t) => t.span,
+        TyKind::Array(a) => a.span,
+        TyKind::Fn(f) => f.span,
+        TyKind::RawPtr(ty) | TyKind::Ref { ty, .. } => Span::combine(start, ty.span),
+        TyKind::Never => start,
+    };
+    Ok(P::new(Ty::new(input.next_id(), span, kind)))
+}
+
 impl Parse for Ty {
     fn parse(input: &mut TokenStream) -> Result<Self> {
-        let kind = TyKind::parse(input)?;
-        let span = match &kind {
-            TyKind::Path(p) => p.span,
-            TyKind::Tuple(t) => t.span,
-            TyKind::Array(a) => a.span,
+        let cp = input.checkpoint();
+        let ty = parse_ty_operand(input)?;
+
+        if input.eat(Kind::RArrow).is_err() {
+            return Ok(*ty);
+        }
+
+        // arrows are right-associative: `A -> B -> C` is `A -> (B -> C)`
+        let ret = Ty::parse(input)?;
+        input.finish_node(cp, NodeKind::FnTy);
+        let span = Span::combine(ty.span, ret.span);
+        Ok(Ty::new(
+            input.next_id(),
+            span,
+            TyKind::Fn(FnTy::new(input.next_id(), span, ty, P::new(ret))),
+        ))
+    }
+}
+
+impl Parse for DelimArgs {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let open = input.eat_from(&[Kind::LParen, Kind::LBrack])?;
+        let (delim, close) = match open.kind() {
+            Kind::LParen => (Delimiter::Paren, Kind::RParen),
+            _ => (Delimiter::Bracket, Kind::RBrack),
+        };
+
+        let mut tokens = Vec::new();
+        loop {
+            match input.peek_kind().ok_or(Error::MissingToken)? {
+                k if k == close => break,
+                Kind::LParen | Kind::LBrack => {
+                    tokens.push(TokenTree::Delimited(DelimArgs::parse(input)?));
+                }
+                Kind::RParen | Kind::RBrack | Kind::Error => return Err(input.unexpected()),
+                _ => tokens.push(TokenTree::Token(input.next().unwrap())),
+            }
+        }
+
+        let close = input.eat(close)?.span();
+        input.finish_node(cp, NodeKind::DelimArgs);
+        Ok(DelimArgs::new(
+            Span::combine(open.span(), close),
+            delim,
+            tokens,
+        ))
+    }
+}
+
+impl Parse for Attribute {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let hash = input.eat(Kind::Hash)?.span();
+        let style = match input.eat(Kind::Bang) {
+            Ok(_) => AttrStyle::Inner,
+            Err(_) => AttrStyle::Outer,
         };
-        Ok(Ty::new(span, kind))
+        input.eat(Kind::LBrack)?;
+        let path = Path::parse(input)?;
+        let args = match input.peek_kind() {
+            Some(Kind::LParen | Kind::LBrack) => Some(DelimArgs::parse(input)?),
+            _ => None,
+        };
+        let rbrack = input.eat(Kind::RBrack)?.span();
+        input.finish_node(cp, NodeKind::Attribute);
+        Ok(Attribute::new(
+            input.next_id(),
+            Span::combine(hash, rbrack),
+            style,
+            path,
+            args,
+        ))
+    }
+}
+
+fn parse_outer_attrs(input: &mut TokenStream) -> Result<Vec<Attribute>> {
+    let mut attrs = Vec::new();
+    while input.peek_kind() == Some(Kind::Hash) {
+        let attr = Attribute::parse(input)?;
+        if attr.style != AttrStyle::Outer {
+            return Err(Error::UnexpectedToken);
+        }
+        attrs.push(attr);
+    }
+    Ok(attrs)
+}
+
+/// Looks ahead for `#!`.
+fn starts_inner_attr(input: &TokenStream) -> bool {
+    let mut input = input.lookahead();
+    input.eat(Kind::Hash).is_ok() && input.eat(Kind::Bang).is_ok()
+}
+
+fn parse_inner_attrs(input: &mut TokenStream) -> Result<Vec<Attribute>> {
+    let mut attrs = Vec::new();
+    while starts_inner_attr(input) {
+        attrs.push(Attribute::parse(input)?);
+    }
+    Ok(attrs)
+}
+
+/// Extends the span of a node to its leading attributes.
+fn with_attrs(attrs: &[Attribute], span: Span) -> Span {
+    match attrs.first() {
+        Some(attr) => Span::combine(attr.span, span),
+        None => span,
     }
 }
 
 impl Parse for Variant {
     fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let attrs = parse_outer_attrs(input)?;
         let name = Ident::parse(input)?;
         let (data, span) = if input.peek().map(|t| t.kind()) == Some(Kind::LParen) {
             let tup = Tuple::parse(input)?;
@@ -124,54 +353,86 @@ impl Parse for Variant {
         } else {
             (Vec::new(), name.span)
         };
-        let span = Span::combine(name.span, span);
-        Ok(Variant::new(span, name, data))
+        input.finish_node(cp, NodeKind::Variant);
+        let span = with_attrs(&attrs, Span::combine(name.span, span));
+        Ok(Variant::new(input.next_id(), span, attrs, name, data))
     }
 }
 
 impl Parse for Enum {
     fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
         let lvert = input.eat(Kind::Vert)?.span();
         let variants = Variant::parse_separated(Kind::Vert, input)?;
+        input.finish_node(cp, NodeKind::Enum);
         let span = if !variants.is_empty() {
             Span::combine(lvert, variants.last().unwrap().span)
         } else {
             lvert
         };
-        Ok(Enum::new(span, variants))
+        Ok(Enum::new(input.next_id(), span, variants))
     }
 }
 
-impl Parse for Vec<Param> {
+impl Parse for ParamGroup {
     fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let attrs = parse_outer_attrs(input)?;
         let lparen = input.eat(Kind::LParen)?.span();
-        let mut idents = Vec::new();
-        while let Ok(ident) = Ident::parse(input) {
-            idents.push(ident);
+        let vis = Visibility::parse(input)?;
+        let mut params = Vec::new();
+        while let Ok(name) = Ident::parse(input) {
+            params.push(Param::new(input.next_id(), name.span, name));
+        }
+
+        if params.is_empty() {
+            return Err(Error::UnexpectedToken);
         }
 
         input.eat(Kind::Colon)?;
 
         let ty = Ty::parse(input)?;
         let rparen = input.eat(Kind::RParen)?.span();
+        input.finish_node(cp, NodeKind::ParamGroup);
 
-        let span = Span::combine(lparen, rparen);
+        let span = with_attrs(&attrs, Span::combine(lparen, rparen));
+        Ok(ParamGroup::new(
+            input.next_id(),
+            span,
+            attrs,
+            vis,
+            params,
+            ty,
+        ))
+    }
+}
 
-        Ok(idents
-            .into_iter()
-            .map(|i| Param::new(span, i, ty.clone()))
-            .collect())
+/// Parses the parameters of a function, which unlike fields can't be `pub`.
+fn parse_params(input: &mut TokenStream) -> Result<Vec<ParamGroup>> {
+    let mut params = Vec::new();
+    while input.peek_kind() == Some(Kind::LParen) {
+        let group = ParamGroup::parse(input)?;
+        if group.vis.is_public() {
+            return Err(Error::UnexpectedToken);
+        }
+        params.push(group);
     }
+    Ok(params)
 }
 
 impl Parse for Record {
     fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
         let rec = input.eat(Kind::Record)?.span();
         let mut fields = Vec::new();
 
-        while matches!(input.peek().map(|t| t.kind()), Some(Kind::LParen)) {
-            fields.append(&mut <Vec<Param>>::parse(input)?);
+        while matches!(
+            input.peek().map(|t| t.kind()),
+            Some(Kind::LParen | Kind::Hash)
+        ) {
+            fields.push(ParamGroup::parse(input)?);
         }
+        input.finish_node(cp, NodeKind::Record);
 
         let span = if !fields.is_empty() {
             Span::combine(rec, fields.last().unwrap().span)
@@ -179,7 +440,7 @@ impl Parse for Record {
             rec
         };
 
-        Ok(Record::new(span, fields))
+        Ok(Record::new(input.next_id(), span, fields))
     }
 }
 
@@ -195,12 +456,1083 @@ impl Parse for TyDefKind {
 
 impl Parse for TyDef {
     fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let vis = Visibility::parse(input)?;
         let ty_kw = input.eat(Kind::Type)?.span();
         let name = Ident::parse(input)?;
         input.eat(Kind::ColonEq)?;
         let ty = TyDefKind::parse(input)?;
         let dot = input.eat(Kind::Dot)?.span();
-        let span = Span::combine(ty_kw, dot);
-        Ok(TyDef::new(span, name, ty))
+        input.finish_node(cp, NodeKind::TyDef);
+        let span = with_vis(vis, Span::combine(ty_kw, dot));
+        Ok(TyDef::new(input.next_id(), span, vis, name, ty))
+    }
+}
+
+/// Splits a number lexeme into its digits, separators included, and its
+/// suffix.
+fn split_suffix(lexeme: &str) -> (&str, &str) {
+    let end = lexeme
+        .find(|c: char| !c.is_ascii_digit() && c != '.' && c != '_')
+        .unwrap_or(lexeme.len());
+    lexeme.split_at(end)
+}
+
+/// Decodes the escape sequences of a char or string literal lexeme.
+fn unescape(lexeme: &str, span: Span) -> Result<String> {
+    let mut chars = lexeme.chars();
+    let mut buf = String::new();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            buf.push(c);
+            continue;
+        }
+
+        buf.push(match chars.next().ok_or(Error::InvalidEscape(span))? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            c @ ('\\' | '\'' | '"') => c,
+            'u' => {
+                let rest = chars.as_str();
+                let code = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(code, _)| code)
+                    .ok_or(Error::InvalidEscape(span))?;
+                chars = rest[code.len() + 2..].chars();
+                u32::from_str_radix(code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(Error::InvalidEscape(span))?
+            }
+            _ => return Err(Error::InvalidEscape(span)),
+        });
+    }
+
+    Ok(buf)
+}
+
+/// Parses a literal, checking integers against the range of their type,
+/// which also depends on whether they are preceded by a `-`.
+fn parse_lit(input: &mut TokenStream, negated: bool) -> Result<Lit> {
+    let token = input.eat_from(LIT_KINDS)?;
+    let span = token.span();
+    let lexeme = interner::get(token.symbol().unwrap());
+
+    let kind = match token.kind() {
+        Kind::IntLit | Kind::FloatLit | Kind::ComplexLit => {
+            let (digits, suffix) = split_suffix(lexeme);
+            let digits = digits.replace('_', "");
+            let float = || digits.parse().map_err(|_| Error::LiteralOutOfRange(span));
+
+            match (token.kind(), suffix) {
+                (Kind::ComplexLit, _) => LitKind::Complex {
+                    re: 0.0,
+                    im: float()?,
+                },
+                (Kind::IntLit, suffix) if FloatTy::from_suffix(suffix).is_none() => {
+                    let value = digits.parse().map_err(|_| Error::LiteralOutOfRange(span))?;
+                    let suffix = match suffix {
+                        "" => None,
+                        s => Some(IntTy::from_suffix(s).ok_or(Error::InvalidSuffix(span))?),
+                    };
+                    if suffix.is_some_and(|ty| value > ty.max_magnitude(negated)) {
+                        return Err(Error::LiteralOutOfRange(span));
+                    }
+                    LitKind::Int { value, suffix }
+                }
+                (_, suffix) => {
+                    let value: f64 = float()?;
+                    let suffix = match suffix {
+                        "" => None,
+                        s => Some(FloatTy::from_suffix(s).ok_or(Error::InvalidSuffix(span))?),
+                    };
+                    if suffix == Some(FloatTy::F32) && (value as f32).is_infinite() {
+                        return Err(Error::LiteralOutOfRange(span));
+                    }
+                    LitKind::Float { value, suffix }
+                }
+            }
+        }
+        Kind::CharLit => {
+            let c = unescape(lexeme, span)?;
+            let mut chars = c.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => LitKind::Char(c),
+                _ => return Err(Error::InvalidEscape(span)),
+            }
+        }
+        _ => LitKind::Str(interner::intern(unescape(lexeme, span)?)),
+    };
+
+    Ok(Lit::new(span, kind))
+}
+
+impl Parse for Lit {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        parse_lit(input, false)
+    }
+}
+
+const LIT_KINDS: &[Kind] = &[
+    Kind::IntLit,
+    Kind::FloatLit,
+    Kind::ComplexLit,
+    Kind::CharLit,
+    Kind::StrLit,
+];
+
+fn starts_pattern_atom(kind: Kind) -> bool {
+    matches!(
+        kind,
+        Kind::Underscore | Kind::Ident | Kind::Root | Kind::LParen | Kind::LBrack | Kind::Minus
+    ) || LIT_KINDS.contains(&kind)
+}
+
+/// Parses a pattern that can appear as a constructor argument without
+/// parentheses, i.e. anything but a constructor applied to arguments or a
+/// `mut` binding.
+fn parse_pattern_atom(input: &mut TokenStream) -> Result<Pattern> {
+    let cp = input.checkpoint();
+    let (pat, node) = match input.peek_kind().ok_or(Error::MissingToken)? {
+        Kind::Underscore => {
+            let span = input.eat(Kind::Underscore)?.span();
+            (
+                Pattern::new(input.next_id(), span, PatternKind::Wild),
+                NodeKind::WildPat,
+            )
+        }
+        Kind::LParen => {
+            let lparen = input.eat(Kind::LParen)?.span();
+            if let Ok(rparen) = input.eat(Kind::RParen) {
+                let span = Span::combine(lparen, rparen.span());
+                input.finish_node(cp, NodeKind::TuplePat);
+                return Ok(Pattern::new(
+                    input.next_id(),
+                    span,
+                    PatternKind::Tuple(Vec::new()),
+                ));
+            }
+
+            let first = Pattern::parse(input)?;
+            if input.eat(Kind::RParen).is_ok() {
+                input.finish_node(cp, NodeKind::ParenPat);
+                return Ok(first);
+            }
+
+            input.eat(Kind::Comma)?;
+            let mut pats = vec![first];
+            pats.append(&mut Pattern::parse_separated(Kind::Comma, input)?);
+            let rparen = input.eat(Kind::RParen)?.span();
+            let span = Span::combine(lparen, rparen);
+            (
+                Pattern::new(input.next_id(), span, PatternKind::Tuple(pats)),
+                NodeKind::TuplePat,
+            )
+        }
+        Kind::LBrack => {
+            let lbrack = input.eat(Kind::LBrack)?.span();
+            let pats = if input.peek_kind() == Some(Kind::RBrack) {
+                Vec::new()
+            } else {
+                Pattern::parse_separated(Kind::Comma, input)?
+            };
+            let rbrack = input.eat(Kind::RBrack)?.span();
+            let span = Span::combine(lbrack, rbrack);
+            (
+                Pattern::new(input.next_id(), span, PatternKind::Array(pats)),
+                NodeKind::ArrayPat,
+            )
+        }
+        Kind::Ident | Kind::Root => {
+            let path = Path::parse(input)?;
+            if path.segments.len() == 1 {
+                let name = path.segments.into_iter().next().unwrap();
+                let binding = Binding::new(input.next_id(), name.span, false, name);
+                (
+                    Pattern::new(input.next_id(), binding.span, PatternKind::Binding(binding)),
+                    NodeKind::BindingPat,
+                )
+            } else {
+                (
+                    Pattern::new(
+                        input.next_id(),
+                        path.span,
+                        PatternKind::Ctor(path, Vec::new()),
+                    ),
+                    NodeKind::CtorPat,
+                )
+            }
+        }
+        Kind::Minus => {
+            let minus = input.eat(Kind::Minus)?.span();
+            let lit = match input.peek_kind() {
+                Some(Kind::IntLit | Kind::FloatLit | Kind::ComplexLit) => parse_lit(input, true)?,
+                _ => return Err(input.unexpected()),
+            };
+            let span = Span::combine(minus, lit.span);
+            (
+                Pattern::new(input.next_id(), span, PatternKind::Neg(lit)),
+                NodeKind::LitPat,
+            )
+        }
+        _ => {
+            let lit = Lit::parse(input)?;
+            (
+                Pattern::new(input.next_id(), lit.span, PatternKind::Lit(lit)),
+                NodeKind::LitPat,
+            )
+        }
+    };
+
+    input.finish_node(cp, node);
+    Ok(pat)
+}
+
+impl Parse for Pattern {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        match input.peek_kind().ok_or(Error::MissingToken)? {
+            Kind::Mut => {
+                let mut_kw = input.eat(Kind::Mut)?.span();
+                let name = Ident::parse(input)?;
+                input.finish_node(cp, NodeKind::BindingPat);
+                let binding = Binding::new(
+                    input.next_id(),
+                    Span::combine(mut_kw, name.span),
+                    true,
+                    name,
+                );
+                Ok(Pattern::new(
+                    input.next_id(),
+                    binding.span,
+                    PatternKind::Binding(binding),
+                ))
+            }
+            Kind::Ident | Kind::Root => {
+                let path = Path::parse(input)?;
+                let mut args = Vec::new();
+                while input.peek_kind().is_some_and(starts_pattern_atom) {
+                    args.push(parse_pattern_atom(input)?);
+                }
+
+                if path.segments.len() == 1 && args.is_empty() {
+                    input.finish_node(cp, NodeKind::BindingPat);
+                    let name = path.segments.into_iter().next().unwrap();
+                    let binding = Binding::new(input.next_id(), name.span, false, name);
+                    return Ok(Pattern::new(
+                        input.next_id(),
+                        binding.span,
+                        PatternKind::Binding(binding),
+                    ));
+                }
+
+                input.finish_node(cp, NodeKind::CtorPat);
+                let span = match args.last() {
+                    Some(arg) => Span::combine(path.span, arg.span),
+                    None => path.span,
+                };
+                Ok(Pattern::new(
+                    input.next_id(),
+                    span,
+                    PatternKind::Ctor(path, args),
+                ))
+            }
+            _ => parse_pattern_atom(input),
+        }
+    }
+}
+
+fn starts_expr(kind: Kind) -> bool {
+    matches!(
+        kind,
+        Kind::Ident
+            | Kind::Root
+            | Kind::LParen
+            | Kind::Begin
+            | Kind::If
+            | Kind::Match
+            | Kind::When
+            | Kind::Loop
+            | Kind::For
+            | Kind::Break
+            | Kind::Continue
+            | Kind::Return
+            | Kind::Minus
+            | Kind::Star
+            | Kind::Amp
+            | Kind::Raw
+    ) || LIT_KINDS.contains(&kind)
+}
+
+/// Whether a token can start an argument of a function application.
+fn starts_arg(kind: Kind) -> bool {
+    matches!(kind, Kind::Ident | Kind::Root | Kind::LParen) || LIT_KINDS.contains(&kind)
+}
+
+fn comparison_op(kind: Kind) -> Option<BinOp> {
+    match kind {
+        Kind::Eq => Some(BinOp::Eq),
+        Kind::Neq => Some(BinOp::Neq),
+        Kind::Lt => Some(BinOp::Lt),
+        Kind::Gt => Some(BinOp::Gt),
+        Kind::LtEq => Some(BinOp::LtEq),
+        Kind::GtEq => Some(BinOp::GtEq),
+        _ => None,
+    }
+}
+
+fn additive_op(kind: Kind) -> Option<BinOp> {
+    match kind {
+        Kind::Plus => Some(BinOp::Add),
+        Kind::Minus => Some(BinOp::Sub),
+        _ => None,
+    }
+}
+
+fn multiplicative_op(kind: Kind) -> Option<BinOp> {
+    match kind {
+        Kind::Star => Some(BinOp::Mul),
+        Kind::Slash => Some(BinOp::Div),
+        _ => None,
+    }
+}
+
+fn binary(input: &mut TokenStream, cp: Checkpoint, op: BinOp, lhs: Expr, rhs: Expr) -> Expr {
+    input.finish_node(cp, NodeKind::BinaryExpr);
+    let span = Span::combine(lhs.span, rhs.span);
+    Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::Binary(op, P::new(lhs), P::new(rhs)),
+    )
+}
+
+/// Tokens closing the statement list of a block.
+const BLOCK_END: &[Kind] = &[Kind::End, Kind::Else, Kind::Elif];
+
+/// Parses the statements following `open` up to (but excluding) one of the
+/// [`BLOCK_END`] tokens.
+fn parse_block(input: &mut TokenStream, open: Span) -> Result<Block> {
+    let cp = input.checkpoint();
+    let mut stmts = Vec::new();
+    let mut expr = None;
+
+    while !input.peek_kind().is_some_and(|k| BLOCK_END.contains(&k)) {
+        if input.peek_kind() == Some(Kind::Let) {
+            let stmt = Let::parse(input)?;
+            stmts.push(Stmt::new(input.next_id(), stmt.span, StmtKind::Let(stmt)));
+            continue;
+        }
+
+        let stmt_cp = input.checkpoint();
+        let e = Expr::parse(input)?;
+        if let Ok(dot) = input.eat(Kind::Dot) {
+            input.finish_node(stmt_cp, NodeKind::ExprStmt);
+            stmts.push(Stmt::new(
+                input.next_id(),
+                Span::combine(e.span, dot.span()),
+                StmtKind::Expr(e),
+            ));
+        } else if e.is_block_like() && !input.peek_kind().is_some_and(|k| BLOCK_END.contains(&k)) {
+            input.finish_node(stmt_cp, NodeKind::ExprStmt);
+            stmts.push(Stmt::new(input.next_id(), e.span, StmtKind::Expr(e)));
+        } else {
+            expr = Some(P::new(e));
+            break;
+        }
+    }
+    input.finish_node(cp, NodeKind::Block);
+
+    let first = stmts
+        .first()
+        .map(|s| s.span)
+        .or(expr.as_ref().map(|e| e.span));
+    let last = expr
+        .as_ref()
+        .map(|e| e.span)
+        .or(stmts.last().map(|s| s.span));
+    let span = match (first, last) {
+        (Some(first), Some(last)) => Span::combine(first, last),
+        _ => Span::new(open.start() + open.len(), 0),
+    };
+
+    Ok(Block::new(input.next_id(), span, stmts, expr))
+}
+
+fn parse_if(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let if_kw = input.eat(Kind::If)?.span();
+    let mut branches = Vec::new();
+    let mut kw = if_kw;
+    let mut branch_cp = cp;
+
+    loop {
+        let cond = Expr::parse(input)?;
+        let then = input.eat(Kind::Then)?.span();
+        let body = parse_block(input, then)?;
+        input.finish_node(branch_cp, NodeKind::IfBranch);
+        branches.push(IfBranch::new(
+            input.next_id(),
+            Span::combine(kw, body.span),
+            cond,
+            body,
+        ));
+
+        branch_cp = input.checkpoint();
+        match input.eat(Kind::Elif) {
+            Ok(elif) => kw = elif.span(),
+            Err(_) => break,
+        }
+    }
+
+    let else_branch = match input.eat(Kind::Else) {
+        Ok(else_kw) => Some(parse_block(input, else_kw.span())?),
+        Err(_) => None,
+    };
+
+    let span = Span::combine(if_kw, input.eat(Kind::End)?.span());
+    input.finish_node(cp, NodeKind::IfExpr);
+    Ok(Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::If(If::new(input.next_id(), span, branches, else_branch)),
+    ))
+}
+
+fn parse_match(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let match_kw = input.eat(Kind::Match)?.span();
+    let scrutinee = Expr::parse(input).map(P::new)?;
+    input.eat(Kind::With)?;
+
+    let mut arms = Vec::new();
+    while input.peek_kind() == Some(Kind::Vert) {
+        let arm_cp = input.checkpoint();
+        let vert = input.eat(Kind::Vert)?;
+        let pat = Pattern::parse(input)?;
+        input.eat(Kind::RFatArrow)?;
+        let body = Expr::parse(input)?;
+        input.finish_node(arm_cp, NodeKind::MatchArm);
+        arms.push(MatchArm::new(
+            input.next_id(),
+            Span::combine(vert.span(), body.span),
+            pat,
+            body,
+        ));
+    }
+
+    let span = Span::combine(match_kw, input.eat(Kind::End)?.span());
+    input.finish_node(cp, NodeKind::MatchExpr);
+    Ok(Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::Match(Match::new(input.next_id(), span, scrutinee, arms)),
+    ))
+}
+
+fn parse_when(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let when_kw = input.eat(Kind::When)?.span();
+
+    let mut arms = Vec::new();
+    while input.peek_kind() == Some(Kind::Vert) {
+        let arm_cp = input.checkpoint();
+        let vert = input.eat(Kind::Vert)?;
+        let cond = Expr::parse(input)?;
+        input.eat(Kind::RFatArrow)?;
+        let body = Expr::parse(input)?;
+        input.finish_node(arm_cp, NodeKind::WhenArm);
+        arms.push(WhenArm::new(
+            input.next_id(),
+            Span::combine(vert.span(), body.span),
+            cond,
+            body,
+        ));
+    }
+
+    let else_branch = match input.eat(Kind::Else) {
+        Ok(else_kw) => Some(parse_block(input, else_kw.span())?),
+        Err(_) => None,
+    };
+
+    let span = Span::combine(when_kw, input.eat(Kind::End)?.span());
+    input.finish_node(cp, NodeKind::WhenExpr);
+    Ok(Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::When(When::new(input.next_id(), span, arms, else_branch)),
+    ))
+}
+
+fn parse_for(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let for_kw = input.eat(Kind::For)?.span();
+    let pat = Pattern::parse(input)?;
+    input.eat(Kind::In)?;
+    let iter = Expr::parse(input).map(P::new)?;
+    let do_kw = input.eat(Kind::Do)?.span();
+    let body = parse_block(input, do_kw)?;
+
+    let span = Span::combine(for_kw, input.eat(Kind::End)?.span());
+    input.finish_node(cp, NodeKind::ForExpr);
+    Ok(Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::For(For::new(input.next_id(), span, pat, iter, body)),
+    ))
+}
+
+/// Looks ahead for `( ident+ :`, which can only start the parameters of a
+/// function literal.
+fn starts_lambda(input: &TokenStream) -> bool {
+    let mut input = input.lookahead();
+    input.eat(Kind::LParen).is_ok()
+        && !input.next_while(|t| t.kind() == Kind::Ident).is_empty()
+        && input.eat(Kind::Colon).is_ok()
+}
+
+fn parse_lambda(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let start = input.peek().ok_or(Error::MissingToken)?.span();
+    let params = parse_params(input)?;
+
+    input.eat(Kind::RFatArrow)?;
+    let body = Expr::parse(input)?;
+    input.finish_node(cp, NodeKind::LambdaExpr);
+    let span = Span::combine(start, body.span);
+    Ok(Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::Lambda(Lambda::new(input.next_id(), span, params, P::new(body))),
+    ))
+}
+
+fn parse_atom(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let (expr, node) = match input.peek_kind().ok_or(Error::MissingToken)? {
+        Kind::LParen if starts_lambda(input) => return parse_lambda(input),
+        Kind::If => return parse_if(input),
+        Kind::Match => return parse_match(input),
+        Kind::When => return parse_when(input),
+        Kind::For => return parse_for(input),
+        Kind::Ident | Kind::Root => {
+            let path = Path::parse(input)?;
+            (
+                Expr::new(input.next_id(), path.span, ExprKind::Path(path)),
+                NodeKind::PathExpr,
+            )
+        }
+        Kind::LParen => {
+            let lparen = input.eat(Kind::LParen)?.span();
+            if let Ok(rparen) = input.eat(Kind::RParen) {
+                let span = Span::combine(lparen, rparen.span());
+                input.finish_node(cp, NodeKind::TupleExpr);
+                return Ok(Expr::new(
+                    input.next_id(),
+                    span,
+                    ExprKind::Tuple(Vec::new()),
+                ));
+            }
+
+            let first = Expr::parse(input)?;
+            if input.eat(Kind::RParen).is_ok() {
+                input.finish_node(cp, NodeKind::ParenExpr);
+                return Ok(first);
+            }
+
+            input.eat(Kind::Comma)?;
+            let mut exprs = vec![first];
+            exprs.append(&mut Expr::parse_separated(Kind::Comma, input)?);
+            let rparen = input.eat(Kind::RParen)?.span();
+            let span = Span::combine(lparen, rparen);
+            (
+                Expr::new(input.next_id(), span, ExprKind::Tuple(exprs)),
+                NodeKind::TupleExpr,
+            )
+        }
+        Kind::Begin => {
+            let begin = input.eat(Kind::Begin)?.span();
+            let block = parse_block(input, begin)?;
+            let span = Span::combine(begin, input.eat(Kind::End)?.span());
+            (
+                Expr::new(input.next_id(), span, ExprKind::Block(block)),
+                NodeKind::BlockExpr,
+            )
+        }
+        Kind::Loop => {
+            let loop_kw = input.eat(Kind::Loop)?.span();
+            let body = parse_block(input, loop_kw)?;
+            let span = Span::combine(loop_kw, input.eat(Kind::End)?.span());
+            (
+                Expr::new(input.next_id(), span, ExprKind::Loop(body)),
+                NodeKind::LoopExpr,
+            )
+        }
+        Kind::Break => {
+            let span = input.eat(Kind::Break)?.span();
+            (
+                Expr::new(input.next_id(), span, ExprKind::Break),
+                NodeKind::BreakExpr,
+            )
+        }
+        Kind::Continue => {
+            let span = input.eat(Kind::Continue)?.span();
+            (
+                Expr::new(input.next_id(), span, ExprKind::Continue),
+                NodeKind::ContinueExpr,
+            )
+        }
+        Kind::Return => {
+            let return_kw = input.eat(Kind::Return)?.span();
+            let expr = if input.peek_kind().is_some_and(starts_expr) {
+                let value = Expr::parse(input)?;
+                let span = Span::combine(return_kw, value.span);
+                Expr::new(input.next_id(), span, ExprKind::Return(Some(P::new(value))))
+            } else {
+                Expr::new(input.next_id(), return_kw, ExprKind::Return(None))
+            };
+            (expr, NodeKind::ReturnExpr)
+        }
+        _ => {
+            let lit = Lit::parse(input)?;
+            (
+                Expr::new(input.next_id(), lit.span, ExprKind::Lit(lit)),
+                NodeKind::LitExpr,
+            )
+        }
+    };
+
+    input.finish_node(cp, node);
+    Ok(expr)
+}
+
+fn parse_call(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let callee = parse_atom(input)?;
+    let mut args = Vec::new();
+    while input.peek_kind().is_some_and(starts_arg) {
+        args.push(parse_atom(input)?);
+    }
+
+    match args.last() {
+        Some(last) => {
+            input.finish_node(cp, NodeKind::CallExpr);
+            let span = Span::combine(callee.span, last.span);
+            Ok(Expr::new(
+                input.next_id(),
+                span,
+                ExprKind::Call(P::new(callee), args),
+            ))
+        }
+        None => Ok(callee),
+    }
+}
+
+fn parse_unary(input: &mut TokenStream) -> Result<Expr> {
+    let op = match input.peek_kind().ok_or(Error::MissingToken)? {
+        Kind::Minus => UnOp::Neg,
+        Kind::Star => UnOp::Deref,
+        Kind::Amp => UnOp::Ref,
+        Kind::Raw => UnOp::Raw,
+        _ => return parse_call(input),
+    };
+
+    let cp = input.checkpoint();
+    let op_span = input.next().ok_or(Error::MissingToken)?.span();
+
+    // negative integer literals can go one further than positive ones
+    if matches!(op, UnOp::Neg) && input.peek_kind() == Some(Kind::IntLit) {
+        let lit_cp = input.checkpoint();
+        let lit = parse_lit(input, true)?;
+        input.finish_node(lit_cp, NodeKind::LitExpr);
+        input.finish_node(cp, NodeKind::UnaryExpr);
+        let operand = Expr::new(input.next_id(), lit.span, ExprKind::Lit(lit));
+        let span = Span::combine(op_span, operand.span);
+        return Ok(Expr::new(
+            input.next_id(),
+            span,
+            ExprKind::Unary(op, P::new(operand)),
+        ));
+    }
+
+    let op = match op {
+        UnOp::Ref if input.eat(Kind::Mut).is_ok() => UnOp::RefMut,
+        op => op,
+    };
+    let operand = parse_unary(input)?;
+    input.finish_node(cp, NodeKind::UnaryExpr);
+    let span = Span::combine(op_span, operand.span);
+    Ok(Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::Unary(op, P::new(operand)),
+    ))
+}
+
+fn parse_multiplicative(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let mut lhs = parse_unary(input)?;
+    while let Some(op) = input.peek_kind().and_then(multiplicative_op) {
+        input.skip();
+        let rhs = parse_unary(input)?;
+        lhs = binary(input, cp, op, lhs, rhs);
+    }
+    Ok(lhs)
+}
+
+fn parse_additive(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let mut lhs = parse_multiplicative(input)?;
+    while let Some(op) = input.peek_kind().and_then(additive_op) {
+        input.skip();
+        let rhs = parse_multiplicative(input)?;
+        lhs = binary(input, cp, op, lhs, rhs);
+    }
+    Ok(lhs)
+}
+
+fn parse_is(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let expr = parse_additive(input)?;
+    if input.eat(Kind::Is).is_err() {
+        return Ok(expr);
+    }
+
+    let pat = Pattern::parse(input)?;
+    input.finish_node(cp, NodeKind::IsExpr);
+    let span = Span::combine(expr.span, pat.span);
+    Ok(Expr::new(
+        input.next_id(),
+        span,
+        ExprKind::Is(P::new(expr), pat),
+    ))
+}
+
+fn parse_comparison(input: &mut TokenStream) -> Result<Expr> {
+    let cp = input.checkpoint();
+    let lhs = parse_is(input)?;
+    match input.peek_kind().and_then(comparison_op) {
+        Some(op) => {
+            input.skip();
+            let rhs = parse_is(input)?;
+            Ok(binary(input, cp, op, lhs, rhs))
+        }
+        None => Ok(lhs),
+    }
+}
+
+impl Parse for Expr {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let place = parse_comparison(input)?;
+        if input.eat(Kind::LArrow).is_err() {
+            return Ok(place);
+        }
+
+        let value = Expr::parse(input)?;
+        input.finish_node(cp, NodeKind::AssignExpr);
+        let span = Span::combine(place.span, value.span);
+        Ok(Expr::new(
+            input.next_id(),
+            span,
+            ExprKind::Assign(P::new(place), P::new(value)),
+        ))
+    }
+}
+
+impl Parse for Let {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let let_kw = input.eat(Kind::Let)?.span();
+        let pat = Pattern::parse(input)?;
+        let ty = match input.eat(Kind::Colon) {
+            Ok(_) => Some(Ty::parse(input)?),
+            Err(_) => None,
+        };
+        input.eat(Kind::ColonEq)?;
+        let init = Expr::parse(input)?;
+        let dot = input.eat(Kind::Dot)?.span();
+        input.finish_node(cp, NodeKind::LetStmt);
+        Ok(Let::new(
+            input.next_id(),
+            Span::combine(let_kw, dot),
+            pat,
+            ty,
+            init,
+        ))
+    }
+}
+
+/// Looks ahead for `(self)`, `(mut self)`, `(&self)` or `(&mut self)`.
+fn starts_receiver(input: &TokenStream) -> bool {
+    let mut input = input.lookahead();
+    if input.eat(Kind::LParen).is_err() {
+        return false;
+    }
+
+    let _ = input.eat(Kind::Amp);
+    let _ = input.eat(Kind::Mut);
+    input
+        .eat(Kind::Ident)
+        .is_ok_and(|t| interner::get(t.symbol().unwrap()) == "self")
+        && input.eat(Kind::RParen).is_ok()
+}
+
+impl Parse for Receiver {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let lparen = input.eat(Kind::LParen)?.span();
+        let kind = match (input.eat(Kind::Amp), input.eat(Kind::Mut)) {
+            (Ok(_), Ok(_)) => ReceiverKind::RefMut,
+            (Ok(_), Err(_)) => ReceiverKind::Ref,
+            (Err(_), Ok(_)) => ReceiverKind::MutValue,
+            (Err(_), Err(_)) => ReceiverKind::Value,
+        };
+        input.eat(Kind::Ident)?;
+        let rparen = input.eat(Kind::RParen)?.span();
+        input.finish_node(cp, NodeKind::Receiver);
+        Ok(Receiver::new(
+            input.next_id(),
+            Span::combine(lparen, rparen),
+            kind,
+        ))
+    }
+}
+
+fn parse_fn(input: &mut TokenStream, allow_receiver: bool) -> Result<FnDef> {
+    let cp = input.checkpoint();
+    let vis = Visibility::parse(input)?;
+    let fun_kw = input.eat(Kind::Fun)?.span();
+    let name = Ident::parse(input)?;
+
+    let receiver = if allow_receiver && starts_receiver(input) {
+        Some(Receiver::parse(input)?)
+    } else {
+        None
+    };
+
+    let params = parse_params(input)?;
+
+    let ret = match input.eat(Kind::Colon) {
+        Ok(_) => Some(Ty::parse(input)?),
+        Err(_) => None,
+    };
+
+    let (body, end) = if input.peek_kind() == Some(Kind::Begin) {
+        let body = parse_atom(input)?;
+        let end = body.span;
+        (body, end)
+    } else {
+        input.eat(Kind::ColonEq)?;
+        let body = Expr::parse(input)?;
+        (body, input.eat(Kind::Dot)?.span())
+    };
+    input.finish_node(cp, NodeKind::FnDef);
+
+    Ok(FnDef::new(
+        input.next_id(),
+        with_vis(vis, Span::combine(fun_kw, end)),
+        vis,
+        name,
+        receiver,
+        params,
+        ret,
+        body,
+    ))
+}
+
+impl Parse for FnDef {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        parse_fn(input, false)
+    }
+}
+
+impl Parse for ConstDef {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let vis = Visibility::parse(input)?;
+        let let_kw = input.eat(Kind::Let)?.span();
+        let name = Ident::parse(input)?;
+        let ty = match input.eat(Kind::Colon) {
+            Ok(_) => Some(Ty::parse(input)?),
+            Err(_) => None,
+        };
+        input.eat(Kind::ColonEq)?;
+        let value = Expr::parse(input)?;
+        let dot = input.eat(Kind::Dot)?.span();
+        input.finish_node(cp, NodeKind::ConstDef);
+        let span = with_vis(vis, Span::combine(let_kw, dot));
+        Ok(ConstDef::new(input.next_id(), span, vis, name, ty, value))
+    }
+}
+
+impl Parse for Method {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let attrs = parse_outer_attrs(input)?;
+        let fn_def = parse_fn(input, true)?;
+        input.finish_node(cp, NodeKind::Method);
+        Ok(Method::new(
+            input.next_id(),
+            with_attrs(&attrs, fn_def.span),
+            attrs,
+            fn_def,
+        ))
+    }
+}
+
+impl Parse for Impl {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let impl_kw = input.eat(Kind::Impl)?.span();
+        let target = Path::parse(input)?;
+
+        let mut methods = Vec::new();
+        while matches!(input.peek_kind(), Some(Kind::Fun | Kind::Pub | Kind::Hash)) {
+            methods.push(Method::parse(input)?);
+        }
+
+        let end = input.eat(Kind::End)?.span();
+        input.finish_node(cp, NodeKind::Impl);
+        Ok(Impl::new(
+            input.next_id(),
+            Span::combine(impl_kw, end),
+            target,
+            methods,
+        ))
+    }
+}
+
+impl Parse for Module {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let module_kw = input.eat(Kind::Module)?.span();
+        let name = Ident::parse(input)?;
+
+        if let Ok(dot) = input.eat(Kind::Dot) {
+            input.finish_node(cp, NodeKind::Module);
+            return Ok(Module::new(
+                input.next_id(),
+                Span::combine(module_kw, dot.span()),
+                name,
+                Vec::new(),
+                None,
+            ));
+        }
+
+        input.eat(Kind::Begin)?;
+        let attrs = parse_inner_attrs(input)?;
+        let mut items = Vec::new();
+        while input.peek_kind() != Some(Kind::End) {
+            items.push(Item::parse(input)?);
+        }
+        let end = input.eat(Kind::End)?.span();
+        input.finish_node(cp, NodeKind::Module);
+        Ok(Module::new(
+            input.next_id(),
+            Span::combine(module_kw, end),
+            name,
+            attrs,
+            Some(items),
+        ))
+    }
+}
+
+impl Parse for Import {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let import_kw = input.eat(Kind::Import)?.span();
+
+        let mut segments = vec![Ident::parse(input)?];
+        let mut kind = ImportKind::Single(None);
+        while input.eat(Kind::Tilde).is_ok() {
+            if input.eat(Kind::Star).is_ok() {
+                kind = ImportKind::Glob;
+                break;
+            }
+            segments.push(Ident::parse(input)?);
+        }
+
+        if matches!(kind, ImportKind::Single(_)) && input.eat(Kind::As).is_ok() {
+            kind = ImportKind::Single(Some(Ident::parse(input)?));
+        }
+
+        let dot = input.eat(Kind::Dot)?.span();
+        input.finish_node(cp, NodeKind::Import);
+        let path = Path::new(
+            input.next_id(),
+            Span::combine(segments[0].span, segments.last().unwrap().span),
+            segments,
+        );
+        Ok(Import::new(
+            input.next_id(),
+            Span::combine(import_kw, dot),
+            path,
+            kind,
+        ))
+    }
+}
+
+impl Parse for Item {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let cp = input.checkpoint();
+        let attrs = parse_outer_attrs(input)?;
+
+        // look past the visibility, which is parsed along with the item
+        let mut lookahead = input.lookahead();
+        let _ = lookahead.eat(Kind::Pub);
+        let kind = match lookahead.peek_kind().ok_or(Error::MissingToken)? {
+            Kind::Type => TyDef::parse(input).map(ItemKind::TyDef)?,
+            Kind::Fun => FnDef::parse(input).map(ItemKind::FnDef)?,
+            Kind::Let => ConstDef::parse(input).map(ItemKind::ConstDef)?,
+            Kind::Impl => Impl::parse(input).map(ItemKind::Impl)?,
+            Kind::Module => Module::parse(input).map(ItemKind::Module)?,
+            Kind::Import => Import::parse(input).map(ItemKind::Import)?,
+            _ => return Err(lookahead.unexpected()),
+        };
+        input.finish_node(cp, NodeKind::Item);
+        let span = match &kind {
+            ItemKind::TyDef(t) => t.span,
+            ItemKind::FnDef(f) => f.span,
+            ItemKind::ConstDef(c) => c.span,
+            ItemKind::Impl(i) => i.span,
+            ItemKind::Module(m) => m.span,
+            ItemKind::Import(i) => i.span,
+        };
+        Ok(Item::new(
+            input.next_id(),
+            with_attrs(&attrs, span),
+            attrs,
+            kind,
+        ))
+    }
+}
+
+impl Parse for SourceFile {
+    fn parse(input: &mut TokenStream) -> Result<Self> {
+        let attrs = parse_inner_attrs(input)?;
+        let mut items = Vec::new();
+        while input.peek().is_some() {
+            items.push(Item::parse(input)?);
+        }
+        input.finish_root(NodeKind::SourceFile);
+
+        let first = attrs
+            .first()
+            .map(|a| a.span)
+            .or(items.first().map(|i| i.span));
+        let last = items
+            .last()
+            .map(|i| i.span)
+            .or(attrs.last().map(|a| a.span));
+        let span = match (first, last) {
+            (Some(first), Some(last)) => Span::combine(first, last),
+            _ => Span::default(),
+        };
+        Ok(SourceFile::new(input.next_id(), span, attrs, items))
     }
 }
@@ -0,0 +1,583 @@
+//! Name resolution: finds the definition each path of a file refers to.
+//!
+//! - Modules, the file included, define the names of their items. Imports
+//!   define the last segment of their path, or their `as` rename, and glob
+//!   imports make the names of a module, or the variants and methods of a
+//!   type, visible behind the module's own names.
+//! - The first segment of a path is looked up among the locals in scope,
+//!   then in the current module and the modules enclosing it, then among the
+//!   built-in types and values. `root` starts the path at the file instead.
+//! - The following segments are looked up in the module or type named so
+//!   far, whose members are its variants and the methods of its impls. Once a
+//!   segment names a value, like a local, the rest access its fields or
+//!   methods, which depend on its type and are left unresolved.
+//! - A lone name in a pattern matches the unit variant or the built-in value
+//!   it names, looked up like the first segment of a path but for locals.
+//!   Otherwise, it binds a local.
+
+use crate::{
+    ast::{
+        visit::{self, Visitor},
+        Binding, Block, Expr, ExprKind, FnDef, For, IfBranch, Impl, Import, ImportKind, Item,
+        ItemKind, Lambda, Let, MatchArm, Method, Module, NodeId, NodeMap, Param, Path, Pattern,
+        PatternKind, Receiver, Record, SourceFile, Ty, TyDefKind, TyKind, WhenArm,
+    },
+    interner::{self, Symbol},
+    span::Span,
+};
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The segment names nothing.
+    Unresolved(Span),
+    /// The name is already defined in the same module or type.
+    Duplicate(Span),
+    /// The import refers back to itself through other imports.
+    ImportCycle(Span),
+    /// The path names something else than what its position calls for, e.g.
+    /// a function used as a type.
+    Expected(Span, &'static str),
+}
+
+/// What a path resolves to. Definitions are given by the id of their node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Res {
+    /// A module, or the file itself.
+    Module(NodeId),
+    Ty(NodeId),
+    /// One of the types built into the language, like `f64`.
+    PrimTy(Symbol),
+    /// One of the values built into the language, `true` or `false`.
+    PrimValue(Symbol),
+    Variant(NodeId),
+    Fn(NodeId),
+    Const(NodeId),
+    /// A param, a binding of a pattern, or `self` by the id of the receiver.
+    Local(NodeId),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Resolution {
+    pub res: Res,
+    /// How many segments of the path lead to `res`. The others access fields
+    /// or methods of its value.
+    pub len: usize,
+}
+
+pub struct Resolutions {
+    /// What each path resolves to, by the id of the path. Paths that don't
+    /// resolve, and those of attributes, are missing.
+    pub paths: NodeMap<Resolution>,
+    /// The lone names of patterns which match a unit variant or a built-in
+    /// value rather than bind a local, by the id of their [`Binding`].
+    pub bindings: NodeMap<Res>,
+    pub errors: Vec<Error>,
+}
+
+/// Resolves the paths of a file. The modules it declares without a body are
+/// taken to be empty, so files of several modules should be
+/// [loaded](crate::load) first.
+pub fn resolve(file: &SourceFile) -> Resolutions {
+    let mut resolver = Resolver {
+        root: file.id,
+        modules: HashMap::new(),
+        members: HashMap::new(),
+        unit_variants: HashSet::new(),
+        imports: HashMap::new(),
+        in_progress: HashSet::new(),
+        module: file.id,
+        scopes: Vec::new(),
+        paths: NodeMap::new(),
+        bindings: NodeMap::new(),
+        errors: Vec::new(),
+    };
+
+    // imports and impls, which need the names of every module
+    let mut later = Vec::new();
+    resolver.collect(file.id, None, &file.items, &mut later);
+    // impls first, so that imports can name methods
+    for &(module, item) in &later {
+        if let ItemKind::Impl(impl_) = &item.kind {
+            resolver.impl_methods(module, impl_);
+        }
+    }
+    for &(module, item) in &later {
+        if let ItemKind::Import(import) = &item.kind {
+            resolver.import(module, import);
+        }
+    }
+    resolver.visit_source_file(file);
+
+    Resolutions {
+        paths: resolver.paths,
+        bindings: resolver.bindings,
+        errors: resolver.errors,
+    }
+}
+
+const PRIM_TYS: &[&str] = &[
+    "bool", "char", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64", "c32",
+    "c64",
+];
+
+const PRIM_VALUES: &[&str] = &["true", "false"];
+
+/// Inserts the value unless `name` already has one, so that the first of
+/// several definitions is kept.
+fn insert_new<T>(map: &mut HashMap<Symbol, T>, name: Symbol, value: T) -> bool {
+    match map.entry(name) {
+        Entry::Occupied(_) => false,
+        Entry::Vacant(entry) => {
+            entry.insert(value);
+            true
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Name<'ast> {
+    Def(Res),
+    Import(&'ast Import),
+}
+
+struct ModuleData<'ast> {
+    parent: Option<NodeId>,
+    names: HashMap<Symbol, Name<'ast>>,
+    globs: Vec<&'ast Import>,
+}
+
+enum Lookup {
+    Found(Res),
+    Missing,
+    /// The name is brought in by an import that failed, which has been
+    /// reported already.
+    Failed,
+}
+
+impl From<Option<Res>> for Lookup {
+    fn from(res: Option<Res>) -> Self {
+        res.map_or(Lookup::Missing, Lookup::Found)
+    }
+}
+
+/// What a path calls for, given where it is.
+#[derive(Clone, Copy)]
+enum Expect {
+    Ty,
+    Value,
+    Ctor,
+    /// A module or a type, whose members a glob import brings in.
+    Members,
+    Any,
+}
+
+struct Resolver<'ast> {
+    root: NodeId,
+    modules: HashMap<NodeId, ModuleData<'ast>>,
+    /// The variants and methods of types.
+    members: HashMap<NodeId, HashMap<Symbol, Res>>,
+    /// The variants without data, which a lone name can match.
+    unit_variants: HashSet<NodeId>,
+    /// What imports resolve to, once they have been resolved.
+    imports: HashMap<NodeId, Option<Res>>,
+    /// The imports being resolved, to catch cycles.
+    in_progress: HashSet<NodeId>,
+    /// The module whose items are being walked.
+    module: NodeId,
+    /// The locals in scope, innermost last.
+    scopes: Vec<HashMap<Symbol, Res>>,
+    paths: NodeMap<Resolution>,
+    bindings: NodeMap<Res>,
+    errors: Vec<Error>,
+}
+
+impl<'ast> Resolver<'ast> {
+    /// Defines the names of a module's items, and of the modules inside.
+    /// Imports and impls are added to `later`, along with their module.
+    fn collect(
+        &mut self,
+        id: NodeId,
+        parent: Option<NodeId>,
+        items: &'ast [Item],
+        later: &mut Vec<(NodeId, &'ast Item)>,
+    ) {
+        let mut data = ModuleData {
+            parent,
+            names: HashMap::new(),
+            globs: Vec::new(),
+        };
+
+        for item in items {
+            let (name, def) = match &item.kind {
+                ItemKind::TyDef(t) => {
+                    if let TyDefKind::Enum(e) = &t.ty {
+                        let mut variants = HashMap::new();
+                        for variant in &e.variants {
+                            let res = Res::Variant(variant.id);
+                            if !insert_new(&mut variants, variant.name.name, res) {
+                                self.errors.push(Error::Duplicate(variant.name.span));
+                            }
+                            if variant.data.is_empty() {
+                                self.unit_variants.insert(variant.id);
+                            }
+                        }
+                        self.members.insert(t.id, variants);
+                    }
+                    (&t.name, Name::Def(Res::Ty(t.id)))
+                }
+                ItemKind::FnDef(d) => (&d.name, Name::Def(Res::Fn(d.id))),
+                ItemKind::ConstDef(c) => (&c.name, Name::Def(Res::Const(c.id))),
+                ItemKind::Module(m) => {
+                    let items = m.items.as_deref().unwrap_or_default();
+                    self.collect(m.id, Some(id), items, later);
+                    (&m.name, Name::Def(Res::Module(m.id)))
+                }
+                ItemKind::Import(i) => {
+                    later.push((id, item));
+                    match &i.kind {
+                        ImportKind::Single(rename) => {
+                            let name = rename.as_ref().or(i.path.segments.last()).unwrap();
+                            (name, Name::Import(i))
+                        }
+                        ImportKind::Glob => {
+                            data.globs.push(i);
+                            continue;
+                        }
+                    }
+                }
+                ItemKind::Impl(_) => {
+                    later.push((id, item));
+                    continue;
+                }
+            };
+            if !insert_new(&mut data.names, name.name, def) {
+                self.errors.push(Error::Duplicate(name.span));
+            }
+        }
+
+        self.modules.insert(id, data);
+    }
+
+    /// Adds the methods of an impl to the members of its type.
+    fn impl_methods(&mut self, module: NodeId, impl_: &'ast Impl) {
+        let ty = match self.path(module, &impl_.target, Expect::Ty) {
+            Some(Res::Ty(ty)) => ty,
+            // built-in types have no members to add methods to
+            Some(_) => {
+                self.errors
+                    .push(Error::Expected(impl_.target.span, "a user-defined type"));
+                return;
+            }
+            None => return,
+        };
+        let members = self.members.entry(ty).or_default();
+        for Method { fn_def, .. } in &impl_.methods {
+            if !insert_new(members, fn_def.name.name, Res::Fn(fn_def.id)) {
+                self.errors.push(Error::Duplicate(fn_def.name.span));
+            }
+        }
+    }
+
+    /// Resolves an import of `module`, unless it already was.
+    fn import(&mut self, module: NodeId, import: &'ast Import) -> Option<Res> {
+        if let Some(&res) = self.imports.get(&import.id) {
+            return res;
+        }
+        if !self.in_progress.insert(import.id) {
+            self.errors.push(Error::ImportCycle(import.span));
+            self.imports.insert(import.id, None);
+            return None;
+        }
+
+        let expect = match import.kind {
+            ImportKind::Single(_) => Expect::Any,
+            ImportKind::Glob => Expect::Members,
+        };
+        let res = self.path(module, &import.path, expect);
+        self.in_progress.remove(&import.id);
+        // a cycle through this import has already settled it
+        *self.imports.entry(import.id).or_insert(res)
+    }
+
+    /// Looks up a name defined in `module` or brought in by its globs.
+    fn lookup_in(&mut self, module: NodeId, name: Symbol) -> Lookup {
+        let data = &self.modules[&module];
+        match data.names.get(&name).copied() {
+            Some(Name::Def(res)) => return Lookup::Found(res),
+            Some(Name::Import(import)) => {
+                return self
+                    .import(module, import)
+                    .map_or(Lookup::Failed, Lookup::Found)
+            }
+            None => {}
+        }
+
+        for glob in data.globs.clone() {
+            // globs being resolved can't bring in the names they rely on
+            if self.in_progress.contains(&glob.id) {
+                continue;
+            }
+            let found = match self.import(module, glob) {
+                Some(Res::Module(from)) => self.lookup_in(from, name),
+                Some(Res::Ty(ty)) => self
+                    .members
+                    .get(&ty)
+                    .and_then(|members| members.get(&name))
+                    .copied()
+                    .into(),
+                _ => Lookup::Missing,
+            };
+            if !matches!(found, Lookup::Missing) {
+                return found;
+            }
+        }
+        Lookup::Missing
+    }
+
+    /// Looks up the first segment of a path from `module`.
+    fn lookup(&mut self, module: NodeId, name: Symbol) -> Lookup {
+        if let Some(&res) = self.scopes.iter().rev().find_map(|scope| scope.get(&name)) {
+            return Lookup::Found(res);
+        }
+        self.lookup_global(module, name)
+    }
+
+    /// Looks up a name from `module`, leaving out locals.
+    fn lookup_global(&mut self, module: NodeId, name: Symbol) -> Lookup {
+        let mut module = Some(module);
+        while let Some(id) = module {
+            match self.lookup_in(id, name) {
+                Lookup::Missing => module = self.modules[&id].parent,
+                found => return found,
+            }
+        }
+        let text = interner::get(name);
+        if PRIM_TYS.contains(&text) {
+            Lookup::Found(Res::PrimTy(name))
+        } else if PRIM_VALUES.contains(&text) {
+            Lookup::Found(Res::PrimValue(name))
+        } else {
+            Lookup::Missing
+        }
+    }
+
+    /// Resolves a path found in `module`, records what it resolves to and
+    /// checks that it fits where it is.
+    fn path(&mut self, module: NodeId, path: &Path, expect: Expect) -> Option<Res> {
+        let (first, rest) = path.segments.split_first().unwrap();
+        let mut res = if interner::get(first.name) == "root" {
+            Res::Module(self.root)
+        } else {
+            let found = self.lookup(module, first.name);
+            self.found(found, first.span)?
+        };
+
+        let mut len = 1;
+        for segment in rest {
+            let member = match res {
+                Res::Module(id) => self.lookup_in(id, segment.name),
+                Res::Ty(id) => self
+                    .members
+                    .get(&id)
+                    .and_then(|members| members.get(&segment.name))
+                    .copied()
+                    .into(),
+                Res::PrimTy(_) => Lookup::Missing,
+                Res::PrimValue(_)
+                | Res::Variant(_)
+                | Res::Fn(_)
+                | Res::Const(_)
+                | Res::Local(_) => break,
+            };
+            res = self.found(member, segment.span)?;
+            len += 1;
+        }
+        self.paths.insert(path.id, Resolution { res, len });
+
+        let fits = match expect {
+            Expect::Ty => matches!(res, Res::Ty(_) | Res::PrimTy(_)),
+            Expect::Value => !matches!(res, Res::Module(_) | Res::PrimTy(_)),
+            Expect::Ctor => matches!(res, Res::Ty(_) | Res::Variant(_)),
+            Expect::Members => matches!(res, Res::Module(_) | Res::Ty(_)),
+            Expect::Any => true,
+        };
+        if !fits {
+            let what = match expect {
+                Expect::Ty => "a type",
+                Expect::Value => "a value",
+                Expect::Ctor => "a constructor",
+                Expect::Members => "a module or a type",
+                Expect::Any => unreachable!(),
+            };
+            self.errors.push(Error::Expected(path.span, what));
+            return None;
+        }
+        Some(res)
+    }
+
+    /// Reports the segment at `span` if nothing was found.
+    fn found(&mut self, lookup: Lookup, span: Span) -> Option<Res> {
+        match lookup {
+            Lookup::Found(res) => Some(res),
+            Lookup::Missing => {
+                self.errors.push(Error::Unresolved(span));
+                None
+            }
+            Lookup::Failed => None,
+        }
+    }
+
+    /// Brings a local into scope. Outside of functions, where only `is` can
+    /// bind, nothing could refer to it.
+    fn define(&mut self, name: Symbol, res: Res) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, res);
+        }
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+}
+
+impl<'ast> Visitor<'ast> for Resolver<'ast> {
+    fn visit_item(&mut self, item: &'ast Item) {
+        // attributes aren't resolved
+        match &item.kind {
+            ItemKind::TyDef(t) => self.visit_ty_def(t),
+            ItemKind::FnDef(d) => self.visit_fn_def(d),
+            ItemKind::ConstDef(c) => self.visit_const_def(c),
+            ItemKind::Impl(i) => self.visit_impl(i),
+            ItemKind::Module(m) => self.visit_module(m),
+            // imports are resolved beforehand
+            ItemKind::Import(_) => {}
+        }
+    }
+
+    fn visit_module(&mut self, module: &'ast Module) {
+        let parent = std::mem::replace(&mut self.module, module.id);
+        for item in module.items.iter().flatten() {
+            self.visit_item(item);
+        }
+        self.module = parent;
+    }
+
+    fn visit_impl(&mut self, impl_: &'ast Impl) {
+        // the target is resolved beforehand, and attributes aren't
+        for method in &impl_.methods {
+            self.visit_fn_def(&method.fn_def);
+        }
+    }
+
+    fn visit_record(&mut self, record: &'ast Record) {
+        // fields aren't locals
+        for group in &record.fields {
+            self.visit_ty(&group.ty);
+        }
+    }
+
+    fn visit_fn_def(&mut self, fn_def: &'ast FnDef) {
+        self.scoped(|this| visit::walk_fn_def(this, fn_def));
+    }
+
+    fn visit_receiver(&mut self, receiver: &'ast Receiver) {
+        self.define(interner::intern("self"), Res::Local(receiver.id));
+    }
+
+    fn visit_param(&mut self, param: &'ast Param) {
+        self.define(param.name.name, Res::Local(param.id));
+    }
+
+    fn visit_binding(&mut self, binding: &'ast Binding) {
+        let name = binding.name.name;
+        if !binding.mutable {
+            match self.lookup_global(self.module, name) {
+                Lookup::Found(res @ Res::Variant(id)) if self.unit_variants.contains(&id) => {
+                    self.bindings.insert(binding.id, res);
+                    return;
+                }
+                // binding the name would hide the variant
+                Lookup::Found(Res::Variant(_)) => {
+                    self.errors
+                        .push(Error::Expected(binding.span, "a unit variant"));
+                    return;
+                }
+                Lookup::Found(res @ Res::PrimValue(_)) => {
+                    self.bindings.insert(binding.id, res);
+                    return;
+                }
+                _ => {}
+            }
+        }
+        self.define(name, Res::Local(binding.id));
+    }
+
+    fn visit_ty(&mut self, ty: &'ast Ty) {
+        match &ty.kind {
+            TyKind::Path(path) => {
+                self.path(self.module, path, Expect::Ty);
+            }
+            _ => visit::walk_ty(self, ty),
+        }
+    }
+
+    fn visit_pattern(&mut self, pat: &'ast Pattern) {
+        if let PatternKind::Ctor(path, _) = &pat.kind {
+            self.path(self.module, path, Expect::Ctor);
+        }
+        visit::walk_pattern(self, pat)
+    }
+
+    fn visit_path(&mut self, _path: &'ast Path) {}
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match &expr.kind {
+            ExprKind::Path(path) => {
+                self.path(self.module, path, Expect::Value);
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.scoped(|this| visit::walk_block(this, block));
+    }
+
+    fn visit_let(&mut self, let_: &'ast Let) {
+        // the bindings are only in scope after the let
+        self.visit_expr(&let_.init);
+        if let Some(ty) = &let_.ty {
+            self.visit_ty(ty);
+        }
+        self.visit_pattern(&let_.pat);
+    }
+
+    fn visit_if_branch(&mut self, branch: &'ast IfBranch) {
+        // the bindings of `is` in the condition are in scope in the body
+        self.scoped(|this| visit::walk_if_branch(this, branch));
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.scoped(|this| visit::walk_match_arm(this, arm));
+    }
+
+    fn visit_when_arm(&mut self, arm: &'ast WhenArm) {
+        self.scoped(|this| visit::walk_when_arm(this, arm));
+    }
+
+    fn visit_for(&mut self, for_: &'ast For) {
+        self.visit_expr(&for_.iter);
+        self.scoped(|this| {
+            this.visit_pattern(&for_.pat);
+            this.visit_block(&for_.body);
+        });
+    }
+
+    fn visit_lambda(&mut self, lambda: &'ast Lambda) {
+        self.scoped(|this| visit::walk_lambda(this, lambda));
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Span {
     start: u32,
     len: u32,
@@ -17,9 +17,45 @@ impl Span {
         self.len
     }
 
+    /// The offset right after the span.
+    pub fn end(self) -> u32 {
+        self.start + self.len
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.len == 0
+    }
+
     pub fn combine(s1: Span, s2: Span) -> Span {
         let start = std::cmp::min(s1.start, s2.start);
         let len = std::cmp::max(s1.start + s1.len - start, s2.start + s2.len - start);
         Span::new(start, len)
     }
 }
+
+/// Maps byte offsets of a source to lines and columns.
+#[derive(Clone, Debug)]
+pub struct LineIndex<'src> {
+    src: &'src str,
+    /// The offset at which each line starts.
+    starts: Vec<u32>,
+}
+
+impl<'src> LineIndex<'src> {
+    pub fn new(src: &'src str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i as u32 + 1))
+            .collect();
+        Self { src, starts }
+    }
+
+    /// The line and column of an offset, both starting at 1. Columns count
+    /// characters, not bytes.
+    pub fn line_col(&self, offset: u32) -> (u32, u32) {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.starts[line] as usize;
+        let end = (offset as usize).min(self.src.len());
+        let col = self.src.get(start..end).map_or(0, |s| s.chars().count());
+        (line as u32 + 1, col as u32 + 1)
+    }
+}
@@ -0,0 +1,324 @@
+mod green;
+mod reparse;
+
+pub use green::{Checkpoint, GreenBuilder, GreenElement, GreenNode, GreenToken};
+pub use reparse::{reparse, Reparsed, ReparsedAst, TextEdit};
+
+use crate::{
+    ast::{NodeId, SourceFile},
+    lexer::Scanner,
+    parser::{self, Parse},
+    span::Span,
+    token::Kind,
+};
+
+use std::{fmt, rc::Rc};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    SourceFile,
+    Item,
+    Attribute,
+    DelimArgs,
+    Visibility,
+    Path,
+
+    // Items
+    TyDef,
+    FnDef,
+    Receiver,
+    ConstDef,
+    Impl,
+    Method,
+    Module,
+    Import,
+
+    // Type definitions
+    Record,
+    Enum,
+    Variant,
+    ParamGroup,
+
+    // Types
+    PathTy,
+    TupleTy,
+    ArrayTy,
+    FnTy,
+    NeverTy,
+    RawPtrTy,
+    RefTy,
+
+    // Patterns
+    WildPat,
+    BindingPat,
+    LitPat,
+    TuplePat,
+    ArrayPat,
+    CtorPat,
+    ParenPat,
+
+    // Statements
+    Block,
+    LetStmt,
+    ExprStmt,
+
+    // Expressions
+    LitExpr,
+    PathExpr,
+    TupleExpr,
+    ParenExpr,
+    CallExpr,
+    UnaryExpr,
+    BinaryExpr,
+    AssignExpr,
+    IsExpr,
+    BlockExpr,
+    IfExpr,
+    IfBranch,
+    MatchExpr,
+    MatchArm,
+    WhenExpr,
+    WhenArm,
+    LoopExpr,
+    ForExpr,
+    BreakExpr,
+    ContinueExpr,
+    ReturnExpr,
+    LambdaExpr,
+}
+
+/// Parses a file into its lossless syntax tree, along with its AST. The ids of
+/// the AST are handed out from `next_id`, which is left past them.
+pub fn parse(src: &str, next_id: &mut NodeId) -> parser::Result<(SyntaxNode, SourceFile)> {
+    let mut input = Scanner::new(src).recording_stream().with_first_id(*next_id);
+    let file = SourceFile::parse(&mut input)?;
+    *next_id = input.next_id();
+    let green = input
+        .into_tree()
+        .expect("the source file should be a single node");
+    Ok((SyntaxNode::new_root(green), file))
+}
+
+/// A node of a syntax tree, i.e. a green node along with its position in the
+/// tree.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: GreenNode,
+    offset: u32,
+    /// The parent of the node and the index of the node in it.
+    parent: Option<(SyntaxNode, usize)>,
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: GreenNode) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            offset: 0,
+            parent: None,
+        }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.green.kind()
+    }
+
+    pub fn green(&self) -> &GreenNode {
+        &self.0.green
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.0.offset, self.0.green.len())
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.as_ref().map(|(parent, _)| parent.clone())
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        let mut offset = self.0.offset;
+        self.0
+            .green
+            .children()
+            .iter()
+            .enumerate()
+            .map(move |(index, child)| {
+                let start = offset;
+                offset += child.len();
+                match child {
+                    GreenElement::Node(green) => {
+                        SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                            green: green.clone(),
+                            offset: start,
+                            parent: Some((self.clone(), index)),
+                        })))
+                    }
+                    GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                        green: green.clone(),
+                        offset: start,
+                        parent: self.clone(),
+                    }),
+                }
+            })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The node and all the nodes below it, in preorder.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let len = stack.len();
+            stack.extend(node.children());
+            stack[len..].reverse();
+            Some(node)
+        })
+    }
+
+    /// The tokens of the node, trivia included, in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => tokens.append(&mut node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    /// Replaces the node with `green` and returns the root of the new tree.
+    /// Everything outside of the node is shared with the original tree.
+    pub fn replace_with(&self, green: GreenNode) -> SyntaxNode {
+        match &self.0.parent {
+            Some((parent, index)) => {
+                let green = parent
+                    .green()
+                    .replace_child(*index, GreenElement::Node(green));
+                parent.replace_with(green)
+            }
+            None => SyntaxNode::new_root(green),
+        }
+    }
+
+    /// Produces the AST of the node, whose spans are relative to the root of
+    /// the tree. Only the text of the node is parsed. Like with [`parse`], ids
+    /// are handed out from `next_id`, so that the ASTs of several nodes can
+    /// share side tables.
+    pub fn to_ast<T: Parse>(&self, next_id: &mut NodeId) -> parser::Result<T> {
+        let text = self.text();
+        let mut input = Scanner::with_offset(&text, self.0.offset)
+            .stream()
+            .with_first_id(*next_id);
+        let ast = T::parse_complete(&mut input)?;
+        *next_id = input.next_id();
+        Ok(ast)
+    }
+}
+
+/// A token of a syntax tree, along with its position.
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: GreenToken,
+    offset: u32,
+    parent: SyntaxNode,
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> Kind {
+        self.green.kind()
+    }
+
+    pub fn green(&self) -> &GreenToken {
+        &self.green
+    }
+
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.green.len())
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+}
+
+#[derive(Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.green)
+    }
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+/// Prints the kind and span of the node, and with `{:#?}` the whole tree below
+/// it, one element per line.
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(
+            f,
+            "{:?}@{}..{}",
+            self.kind(),
+            span.start(),
+            span.start() + span.len()
+        )?;
+        if !f.alternate() {
+            return Ok(());
+        }
+
+        fn go(node: &SyntaxNode, depth: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for child in node.children_with_tokens() {
+                write!(f, "\n{:width$}", "", width = 2 * depth)?;
+                match child {
+                    SyntaxElement::Node(node) => {
+                        write!(f, "{node:?}")?;
+                        go(&node, depth + 1, f)?;
+                    }
+                    SyntaxElement::Token(token) => write!(f, "{token:?}")?,
+                }
+            }
+            Ok(())
+        }
+
+        go(self, 1, f)
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span = self.span();
+        write!(
+            f,
+            "{:?}@{}..{} {:?}",
+            self.kind(),
+            span.start(),
+            span.start() + span.len(),
+            self.text()
+        )
+    }
+}
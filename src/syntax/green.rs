@@ -0,0 +1,159 @@
+use super::NodeKind;
+use crate::token::Kind;
+
+use std::{fmt, rc::Rc};
+
+/// A token of a green tree, which knows its text but not its position. The
+/// text is owned by the tree rather than interned, so that it goes away along
+/// with the trees using it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GreenToken {
+    kind: Kind,
+    text: Rc<str>,
+}
+
+impl GreenToken {
+    pub fn new(kind: Kind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn len(&self) -> u32 {
+        u32::try_from(self.text.len()).unwrap()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+/// An immutable node of a green tree, which can be shared between trees.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GreenNode(Rc<GreenNodeData>);
+
+#[derive(Debug, PartialEq)]
+struct GreenNodeData {
+    kind: NodeKind,
+    len: u32,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+        Self(Rc::new(GreenNodeData {
+            kind,
+            len,
+            children,
+        }))
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        self.0.kind
+    }
+
+    /// The length of the text of the node.
+    pub fn len(&self) -> u32 {
+        self.0.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.0.children
+    }
+
+    /// A copy of the node with its child at `index` replaced. The other
+    /// children are shared with the original node.
+    pub fn replace_child(&self, index: usize, child: GreenElement) -> GreenNode {
+        let mut children = self.0.children.clone();
+        children[index] = child;
+        GreenNode::new(self.kind(), children)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GreenElement {
+    Node(GreenNode),
+    Token(GreenToken),
+}
+
+impl GreenElement {
+    pub fn len(&self) -> u32 {
+        match self {
+            GreenElement::Node(node) => node.len(),
+            GreenElement::Token(token) => token.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for GreenToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.children().iter().try_for_each(|child| match child {
+            GreenElement::Node(node) => write!(f, "{node}"),
+            GreenElement::Token(token) => write!(f, "{token}"),
+        })
+    }
+}
+
+/// A position in the children being built, where a node can later start.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Checkpoint(usize);
+
+/// Builds a green tree bottom-up: tokens are added as they are parsed and
+/// nodes wrap everything added since a checkpoint.
+#[derive(Debug, Default)]
+pub struct GreenBuilder {
+    children: Vec<GreenElement>,
+}
+
+impl GreenBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn token(&mut self, kind: Kind, text: &str) {
+        self.children
+            .push(GreenElement::Token(GreenToken::new(kind, text)));
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.children.len())
+    }
+
+    pub fn finish_node(&mut self, checkpoint: Checkpoint, kind: NodeKind) {
+        let children = self.children.drain(checkpoint.0..).collect();
+        self.children
+            .push(GreenElement::Node(GreenNode::new(kind, children)));
+    }
+
+    /// The root of the tree, if everything was wrapped in a single node.
+    pub fn finish(mut self) -> Option<GreenNode> {
+        match (self.children.pop(), self.children.is_empty()) {
+            (Some(GreenElement::Node(root)), true) => Some(root),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use super::{NodeKind, SyntaxElement, SyntaxNode, SyntaxToken};
+use crate::{
+    ast::{mut_visit::MutVisitor, Item, ItemKind, NodeId, SourceFile, P},
+    lexer::Scanner,
+    parser::{self, Parse},
+    span::Span,
+};
+
+/// Replaces a span of the text of a file.
+#[derive(Clone, Debug)]
+pub struct TextEdit {
+    pub span: Span,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(span: Span, text: impl Into<String>) -> Self {
+        Self {
+            span,
+            text: text.into(),
+        }
+    }
+
+    /// Applies the edit to `text`, whose span starts at `offset`.
+    fn apply(&self, text: &str, offset: u32) -> String {
+        let start = usize::try_from(self.span.start() - offset).unwrap();
+        let end = start + usize::try_from(self.span.len()).unwrap();
+        let mut buf = String::with_capacity(text.len() - (end - start) + self.text.len());
+        buf.push_str(&text[..start]);
+        buf.push_str(&self.text);
+        buf.push_str(&text[end..]);
+        buf
+    }
+}
+
+/// The tree after an edit, and what changed in the AST of the file.
+pub struct Reparsed {
+    pub root: SyntaxNode,
+    pub ast: ReparsedAst,
+}
+
+pub enum ReparsedAst {
+    /// Only the item which spanned `old` before the edit was reparsed.
+    Item { old: Span, item: P<Item> },
+    /// The whole file was reparsed.
+    File(SourceFile),
+}
+
+impl ReparsedAst {
+    /// Brings `file`, the AST of the text before the edit, up to date. When
+    /// only an item was reparsed, the other items are kept along with their
+    /// ids, and those after the edit are moved past it.
+    pub fn apply(self, file: &mut SourceFile) {
+        match self {
+            ReparsedAst::Item { old, item } => replace_item(file, old, *item),
+            ReparsedAst::File(new) => *file = new,
+        }
+    }
+}
+
+/// Replaces the item which spanned `old` with its reparsed version.
+fn replace_item(file: &mut SourceFile, old: Span, item: Item) {
+    let path = item_path(&file.items, old)
+        .expect("the file should be the AST of the text before the edit");
+    let delta = i64::from(item.span.len()) - i64::from(old.len());
+    MoveSpans {
+        after: end(old),
+        delta,
+    }
+    .visit_source_file(file);
+
+    let (&last, path) = path.split_last().unwrap();
+    let items = path.iter().fold(&mut file.items, |items, &index| {
+        match &mut items[index].kind {
+            ItemKind::Module(module) => module.items.as_mut().unwrap(),
+            _ => unreachable!("only modules contain items"),
+        }
+    });
+    items[last] = item;
+}
+
+/// The indices of the items leading to the one spanning `span`, through the
+/// modules containing it.
+fn item_path(items: &[Item], span: Span) -> Option<Vec<usize>> {
+    items.iter().enumerate().find_map(|(index, item)| {
+        if item.span == span {
+            return Some(vec![index]);
+        }
+        let ItemKind::Module(module) = &item.kind else {
+            return None;
+        };
+        let mut path = item_path(module.items.as_deref()?, span)?;
+        path.insert(0, index);
+        Some(path)
+    })
+}
+
+/// Moves the spans starting after an edit, and stretches those containing it.
+struct MoveSpans {
+    after: u32,
+    delta: i64,
+}
+
+impl MutVisitor for MoveSpans {
+    fn visit_span(&mut self, span: &mut Span) {
+        let moved = |offset: u32| u32::try_from(i64::from(offset) + self.delta).unwrap();
+        if span.start() >= self.after {
+            *span = Span::new(moved(span.start()), span.len());
+        } else if end(*span) >= self.after {
+            *span = Span::new(span.start(), moved(span.len()));
+        }
+    }
+}
+
+/// Applies `edit` to the tree rooted at `root`. When the edit is confined to
+/// an item, only that item is reparsed and the rest of the tree is reused.
+/// Ids are handed out from `next_id` as with [`parse`](super::parse).
+pub fn reparse(
+    root: &SyntaxNode,
+    edit: &TextEdit,
+    next_id: &mut NodeId,
+) -> parser::Result<Reparsed> {
+    if let Some(reparsed) =
+        enclosing_item(root, edit.span).and_then(|item| reparse_item(&item, edit, next_id))
+    {
+        return Ok(reparsed);
+    }
+
+    let (root, file) = super::parse(&edit.apply(&root.text(), 0), next_id)?;
+    Ok(Reparsed {
+        root,
+        ast: ReparsedAst::File(file),
+    })
+}
+
+fn end(span: Span) -> u32 {
+    span.start() + span.len()
+}
+
+/// The innermost item whose text strictly contains `span`, so that its first
+/// and last characters are left untouched by an edit of `span`.
+fn enclosing_item(root: &SyntaxNode, span: Span) -> Option<SyntaxNode> {
+    let mut item = None;
+    let mut node = root.clone();
+
+    loop {
+        let child = node.children().find(|child| {
+            let outer = child.span();
+            outer.start() < span.start() && end(span) < end(outer)
+        });
+
+        match child {
+            Some(child) => {
+                if child.kind() == NodeKind::Item {
+                    item = Some(child.clone());
+                }
+                node = child;
+            }
+            None => return item,
+        }
+    }
+}
+
+/// Reparses an item after an edit, unless the edit changes what the item
+/// spans. Text that doesn't lex or parse as an item is left to a reparse of
+/// the whole file.
+fn reparse_item(item: &SyntaxNode, edit: &TextEdit, next_id: &mut NodeId) -> Option<Reparsed> {
+    let start = item.span().start();
+    let text = edit.apply(&item.text(), start);
+
+    // the text after the item is left untouched by the edit
+    let next = next_token(item);
+    let next = next.as_ref().map_or("", SyntaxToken::text);
+    if !ends_token(&format!("{text}{next}"), start, text.len()) {
+        return None;
+    }
+
+    let mut input = Scanner::with_offset(&text, start)
+        .recording_stream()
+        .with_first_id(*next_id);
+    let parsed = Item::parse_complete(&mut input).ok()?;
+    // trivia after the item would be left out of it
+    if end(parsed.span) != start + u32::try_from(text.len()).unwrap() {
+        return None;
+    }
+    *next_id = input.next_id();
+    let green = input.into_tree()?;
+
+    Some(Reparsed {
+        root: item.replace_with(green),
+        ast: ReparsedAst::Item {
+            old: item.span(),
+            item: P::new(parsed),
+        },
+    })
+}
+
+/// The first token among `elements` and their descendants.
+fn first_token(elements: impl Iterator<Item = SyntaxElement>) -> Option<SyntaxToken> {
+    elements
+        .filter_map(|element| match element {
+            SyntaxElement::Token(token) => Some(token),
+            SyntaxElement::Node(node) => first_token(node.children_with_tokens()),
+        })
+        .next()
+}
+
+/// The token right after `node` in the tree, trivia included.
+fn next_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    let (parent, index) = node.0.parent.as_ref()?;
+    first_token(parent.children_with_tokens().skip(index + 1)).or_else(|| next_token(parent))
+}
+
+/// Whether a token of `text`, lexed from offset `start`, ends exactly `len`
+/// bytes in, i.e. the last token of the item isn't merged with what follows
+/// it, as with a comment running past the item.
+fn ends_token(text: &str, start: u32, len: usize) -> bool {
+    let end = start + u32::try_from(len).unwrap();
+    let mut scanner = Scanner::with_offset(text, start).with_trivia();
+    std::iter::from_fn(|| scanner.scan())
+        .map(|t| t.span().start() + t.span().len())
+        .find(|&e| e >= end)
+        == Some(end)
+}
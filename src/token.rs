@@ -1,7 +1,7 @@
 use crate::interner::{get, Symbol};
 use crate::span::Span;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Kind {
     // 1 symbol tokens
     LBrack,
@@ -17,6 +17,7 @@ pub enum Kind {
     Amp,
     Plus,
     Hash,
+    Bang,
 
     // Ambiguous tokens
     Colon,
@@ -45,6 +46,13 @@ pub enum Kind {
     StrLit,
     Ident,
 
+    // Trivia
+    Whitespace,
+    Comment,
+
+    /// Text that isn't a token, like an unterminated string literal.
+    Error,
+
     // Keywords
     Char,
     Bool,
@@ -83,15 +91,20 @@ pub enum Kind {
     Do,
     Module,
     Import,
+    As,
     Root,
     When,
+    Break,
+    Continue,
+    Return,
 }
 
 impl Kind {
     pub fn repr(self) -> Option<&'static str> {
         use Kind::*;
         match self {
-            IntLit | FloatLit | ComplexLit | CharLit | StrLit | Ident => None,
+            IntLit | FloatLit | ComplexLit | CharLit | StrLit | Ident | Whitespace | Comment
+            | Error => None,
             LBrack => Some("["),
             RBrack => Some("]"),
             LParen => Some("("),
@@ -105,6 +118,7 @@ impl Kind {
             Amp => Some("&"),
             Plus => Some("+"),
             Hash => Some("#"),
+            Bang => Some("!"),
             Colon => Some(":"),
             Underscore => Some("_"),
             Minus => Some("-"),
@@ -116,8 +130,8 @@ impl Kind {
             GtEq => Some(">="),
             Neq => Some("/="),
             ColonEq => Some(":="),
-            LArrow => Some("->"),
-            RArrow => Some("<-"),
+            LArrow => Some("<-"),
+            RArrow => Some("->"),
             RFatArrow => Some("=>"),
             Char => Some("char"),
             Bool => Some("bool"),
@@ -156,8 +170,12 @@ impl Kind {
             Do => Some("do"),
             Module => Some("module"),
             Import => Some("import"),
+            As => Some("as"),
             Root => Some("root"),
             When => Some("when"),
+            Break => Some("break"),
+            Continue => Some("continue"),
+            Return => Some("return"),
         }
     }
 
@@ -201,29 +219,77 @@ impl Kind {
             "do" => Do,
             "module" => Module,
             "import" => Import,
+            "as" => As,
             "root" => Root,
             "when" => When,
+            "break" => Break,
+            "continue" => Continue,
+            "return" => Return,
             _ => Ident,
         }
     }
 
+    /// Whether the token is only kept in lossless syntax trees.
+    pub fn is_trivia(self) -> bool {
+        matches!(self, Kind::Whitespace | Kind::Comment)
+    }
+
     pub fn is_kw(self) -> bool {
         use Kind::*;
-        match self {
-            Char | Bool | U8 | U16 | U32 | U64 | I8 | I16 | I32 | I64 | F32 | F64 | C32 | C64
-            | Raw | Type | Record | Pub | Let | Mut | Fun | Begin | End | Impl | If | Is | Then
-            | Elif | Else | Match | With | Loop | For | In | Do | Module | Import | Root | When => {
-                true
-            }
-            _ => false,
-        }
+        matches!(
+            self,
+            Char | Bool
+                | U8
+                | U16
+                | U32
+                | U64
+                | I8
+                | I16
+                | I32
+                | I64
+                | F32
+                | F64
+                | C32
+                | C64
+                | Raw
+                | Type
+                | Record
+                | Pub
+                | Let
+                | Mut
+                | Fun
+                | Begin
+                | End
+                | Impl
+                | If
+                | Is
+                | Then
+                | Elif
+                | Else
+                | Match
+                | With
+                | Loop
+                | For
+                | In
+                | Do
+                | Module
+                | Import
+                | As
+                | Root
+                | When
+                | Break
+                | Continue
+                | Return
+        )
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Token {
     kind: Kind,
     span: Span,
+    /// The text of identifiers, literals and errors. That of trivia is left in
+    /// the source instead of being interned.
     symbol: Option<Symbol>,
 }
 
@@ -246,10 +312,9 @@ impl Token {
 
     pub fn repr(self) -> &'static str {
         self.kind().repr().unwrap_or_else(|| {
-            get(self.symbol().expect(&format!(
-                "token '{:?}' should have a lexeme but doesn't",
-                self.kind(),
-            )))
+            get(self.symbol().unwrap_or_else(|| {
+                panic!("token '{:?}' should have a lexeme but doesn't", self.kind())
+            }))
         })
     }
 }
@@ -0,0 +1,95 @@
+//! Generates random sources, to test that trees survive being printed.
+
+/// A xorshift generator, so that failures can be replayed.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+
+    pub fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[self.below(choices.len())]
+    }
+
+    pub fn ident(&mut self) -> &'static str {
+        self.pick(&["x", "y", "Point", "f"])
+    }
+
+    pub fn lit(&mut self) -> &'static str {
+        self.pick(&[
+            "0", "42i8", "1.5", "2f32", "0.5i", "'c'", "'\\n'", "\"s t\"", "\"\\\"\"",
+        ])
+    }
+
+    pub fn ty(&mut self, depth: usize) -> String {
+        match if depth == 0 { 0 } else { self.below(6) } {
+            0 => self.pick(&["i32", "f64", "Point", "A~B", "()"]).to_string(),
+            1 => format!("({}, {})", self.ty(depth - 1), self.ty(depth - 1)),
+            2 => format!("[{}; {}]", self.ty(depth - 1), self.expr(depth - 1)),
+            3 => format!("({}) -> {}", self.ty(depth - 1), self.ty(depth - 1)),
+            4 => format!("&mut {}", self.ty(depth - 1)),
+            _ => format!("raw {}", self.ty(depth - 1)),
+        }
+    }
+
+    pub fn pattern(&mut self, depth: usize) -> String {
+        match if depth == 0 {
+            self.below(3)
+        } else {
+            self.below(6)
+        } {
+            0 => "_".to_string(),
+            1 => format!("mut {}", self.ident()),
+            2 => self.lit().to_string(),
+            3 => format!("({}, {})", self.pattern(depth - 1), self.pattern(depth - 1)),
+            4 => format!("[{}]", self.pattern(depth - 1)),
+            _ => format!("Point ({}) {}", self.pattern(depth - 1), self.ident()),
+        }
+    }
+
+    pub fn expr(&mut self, depth: usize) -> String {
+        match if depth == 0 {
+            self.below(2)
+        } else {
+            self.below(11)
+        } {
+            0 => self.ident().to_string(),
+            1 => self.lit().to_string(),
+            2 => {
+                let op = self.pick(&["+", "-", "*", "/", "=", "/=", "<", ">", "<=", ">="]);
+                format!("({} {op} {})", self.expr(depth - 1), self.expr(depth - 1))
+            }
+            3 => {
+                let op = self.pick(&["-", "*", "&", "&mut ", "raw "]);
+                format!("({op}{})", self.expr(depth - 1))
+            }
+            4 => format!("(f {} {})", self.expr(depth - 1), self.expr(depth - 1)),
+            5 => format!("({}, {})", self.expr(depth - 1), self.expr(depth - 1)),
+            6 => format!(
+                "(begin let {} : {} := {}. {} end)",
+                self.pattern(depth - 1),
+                self.ty(depth - 1),
+                self.expr(depth - 1),
+                self.expr(depth - 1)
+            ),
+            7 => format!(
+                "(if {} then {} else {} end)",
+                self.expr(depth - 1),
+                self.expr(depth - 1),
+                self.expr(depth - 1)
+            ),
+            8 => format!(
+                "(match {} with | {} => {} end)",
+                self.expr(depth - 1),
+                self.pattern(depth - 1),
+                self.expr(depth - 1)
+            ),
+            9 => format!("({} is {})", self.expr(depth - 1), self.pattern(depth - 1)),
+            _ => format!("((x: {}) => {})", self.ty(depth - 1), self.expr(depth - 1)),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use melange::{
+    ast::{
+        visit::{self, Visitor},
+        Array, SourceFile,
+    },
+    const_eval::{array_lens, Error},
+    parser::parse_str,
+    resolve::resolve,
+    span::Span,
+};
+
+#[derive(Default)]
+struct Arrays<'ast>(Vec<&'ast Array>);
+
+impl<'ast> Visitor<'ast> for Arrays<'ast> {
+    fn visit_array(&mut self, array: &'ast Array) {
+        self.0.push(array);
+        visit::walk_array(self, array);
+    }
+}
+
+/// The lengths of the arrays of `src` in order, `-` for those with errors,
+/// and the errors with the text at their span.
+fn lens(src: &str) -> (Vec<String>, Vec<String>) {
+    let file: SourceFile = parse_str(src).unwrap();
+    let resolutions = resolve(&file);
+    assert_eq!(resolutions.errors, []);
+    let lens = array_lens(&file, &resolutions);
+
+    let mut arrays = Arrays::default();
+    arrays.visit_source_file(&file);
+    let found = arrays
+        .0
+        .iter()
+        .map(|array| match lens.lens.get(array.id) {
+            Some(len) => len.to_string(),
+            None => "-".to_string(),
+        })
+        .collect();
+
+    let text = |span: Span| &src[span.start() as usize..span.end() as usize];
+    let errors = lens
+        .errors
+        .iter()
+        .map(|error| match *error {
+            Error::NotConstant(span) => format!("{} isn't constant", text(span)),
+            Error::UnknownConstant(span) => format!("unknown {}", text(span)),
+            Error::Cycle(span) => format!("cycle {}", text(span)),
+            Error::Overflow(span) => format!("{} overflows", text(span)),
+            Error::DivisionByZero(span) => format!("{} divides by zero", text(span)),
+            Error::NegativeLength(span) => format!("{} is negative", text(span)),
+            Error::NotInteger(span) => format!("{} isn't an integer type", text(span)),
+        })
+        .collect();
+    (found, errors)
+}
+
+#[test]
+fn valid_lengths() {
+    let src = "\
+let N : u32 := 4.
+module M begin
+    pub let K := N * 2 + 1.
+end
+import M~K.
+type Block := [u8; 4 * 4].
+type Grid := [[f64; N]; K].
+fun f (x: [char; N - 4]) : [u8; M~K / 3] := x.
+";
+    let (lens, errors) = lens(src);
+    assert_eq!(errors, [] as [String; 0]);
+    assert_eq!(lens, ["16", "9", "4", "0", "3"]);
+}
+
+#[test]
+fn errors() {
+    let src = "\
+let A := B + 1.
+let B := A.
+let Big : u8 := 200 + 100.
+let F : f64 := 1.
+let Zero := 0.
+fun len := 4.
+type T := [u8; A].
+type U := [u8; Big].
+type V := [u8; F].
+type W := [u8; len].
+type X := [u8; 2 < 3].
+type Y := [u8; 1 - 2].
+type Z := [u8; 4 / Zero].
+fun f (n: u64) : [u8; n] := f n.
+type Fine := [u8; 2].
+type Again := [u8; Big].
+";
+    let (lens, errors) = lens(src);
+    assert_eq!(
+        errors,
+        [
+            "cycle A",
+            "200 + 100 overflows",
+            "f64 isn't an integer type",
+            "len isn't constant",
+            "2 < 3 isn't constant",
+            "1 - 2 is negative",
+            "4 / Zero divides by zero",
+            "n isn't constant",
+        ]
+    );
+    assert_eq!(lens, ["-", "-", "-", "-", "-", "-", "-", "-", "2", "-"]);
+}
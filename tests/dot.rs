@@ -0,0 +1,54 @@
+use melange::{
+    ast::{dot::to_dot, Expr, SourceFile},
+    parser::parse_str,
+};
+
+use std::process::Command;
+
+#[test]
+fn nodes_and_edges() {
+    let src = "f\n  (x + \"a\\\"b\")";
+    let expr: Expr = parse_str(src).unwrap();
+    let dot = to_dot(&expr, src);
+
+    assert!(dot.starts_with("digraph ast {\n"));
+    assert!(dot.ends_with("}\n"));
+    let lines: Vec<&str> = dot.lines().map(str::trim).collect();
+    assert!(lines.contains(&r#"n0 [label="Expr #6\l1:1-2:14\lkind = Call\l"];"#));
+    assert!(lines.contains(&r#"n1 [label="Expr #1\l1:1-1:2\lkind = Path\l"];"#));
+    assert!(lines.contains(&r#"n2 [label="Path #0\l1:1-1:2\lsegments[0] = f\l"];"#));
+    assert!(lines.contains(&r#"n0 -> n1 [label="callee"];"#));
+    assert!(lines.contains(&r#"n0 -> n3 [label="args[0]"];"#));
+    assert!(lines.contains(&r#"n3 [label="Expr #5\l2:4-2:14\lkind = Binary\lop = Add\l"];"#));
+    assert!(lines.contains(&r#"n3 -> n6 [label="rhs"];"#));
+    assert!(lines
+        .iter()
+        .any(|line| line.contains(r#"kind = Str\lvalue = a\"b\l"#)));
+    assert_eq!(lines.iter().filter(|line| line.contains("->")).count(), 7);
+}
+
+#[test]
+fn emit_option() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/points.l1");
+    let src = std::fs::read_to_string(path).unwrap();
+    let file: SourceFile = parse_str(&src).unwrap();
+
+    let emit = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_melange"))
+            .args(args)
+            .output()
+            .unwrap();
+        (
+            output.status.success(),
+            String::from_utf8(output.stdout).unwrap(),
+        )
+    };
+    assert_eq!(emit(&[path]), (true, format!("{file}\n")));
+    assert_eq!(emit(&["--emit", "dot", path]), (true, to_dot(&file, &src)));
+    assert_eq!(emit(&["--emit=sexp", path]), (true, format!("{file}\n")));
+    assert!(emit(&["--emit", "json", path])
+        .1
+        .starts_with("{\n  \"node\": \"SourceFile\""));
+    assert!(!emit(&["--emit", "xml", path]).0);
+    assert!(!emit(&[]).0);
+}
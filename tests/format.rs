@@ -0,0 +1,290 @@
+use melange::{
+    ast::{
+        format::{format_source, WIDTH},
+        SourceFile, StructuralEq,
+    },
+    parser::parse_str,
+};
+
+use std::{fs, process::Command};
+
+mod common;
+
+use common::Rng;
+
+/// Formats `src`, and checks that the result parses to the same tree and is
+/// left unchanged by formatting it again.
+fn check(src: &str, width: usize) -> String {
+    let file: SourceFile =
+        parse_str(src).unwrap_or_else(|err| panic!("couldn't parse {src}: {err:?}"));
+    let formatted = format_source(src, width).unwrap();
+    let reparsed: SourceFile = parse_str(&formatted)
+        .unwrap_or_else(|err| panic!("couldn't parse the formatted {src}:\n{formatted}\n{err:?}"));
+    assert!(
+        file.structurally_eq(&reparsed),
+        "{src} was formatted to a different tree:\n{formatted}"
+    );
+    assert_eq!(
+        format_source(&formatted, width).unwrap(),
+        formatted,
+        "formatting {src} isn't idempotent"
+    );
+    formatted
+}
+
+#[test]
+fn layout() {
+    let src = "\
+type Point:=record(x y:f64)  (z:f64).
+type Shape := | Circle (Point, f64) | Square (Point, Point).
+type Unit := | Unit.
+fun area (s: Shape) : f64 := match s with | Shape~Circle _ r => 3.14 * r * r | Shape~Square (Point x1 y1 _) (Point x2 y2 _) => (x2 - x1) * (y2 - y1) end.
+fun f (x y: f64) begin let p := Point x y (x+y). if x<y then print x. else print y. print x. end
+loop
+end. p~x <- 2.0 end
+impl Point #[inline]   fun neg (self) := Point (-self~x) (-self~y) (-self~z). end
+";
+    assert_eq!(
+        check(src, WIDTH),
+        "\
+type Point := record (x y: f64) (z: f64).
+type Shape :=
+    | Circle (Point, f64)
+    | Square (Point, Point).
+type Unit := | Unit.
+fun area (s: Shape) : f64 :=
+    match s with
+    | Shape~Circle _ r => 3.14 * r * r
+    | Shape~Square (Point x1 y1 _) (Point x2 y2 _) => (x2 - x1) * (y2 - y1)
+    end.
+fun f (x y: f64) begin
+    let p := Point x y (x + y).
+    if x < y then
+        print x.
+    else
+        print y.
+        print x.
+    end
+    loop end.
+    p~x <- 2.0
+end
+impl Point
+    #[inline]
+    fun neg (self) := Point (-self~x) (-self~y) (-self~z).
+end
+"
+    );
+}
+
+#[test]
+fn width() {
+    let src = "let N := f (g a b) (h c d) + long_name * (other + 1) - 2.";
+    assert_eq!(check(src, WIDTH), format!("{src}\n"));
+    assert_eq!(
+        check(src, 30),
+        "\
+let N :=
+    f (g a b) (h c d)
+    + long_name * (other + 1)
+    - 2.
+"
+    );
+    assert_eq!(
+        check(src, 21),
+        "\
+let N :=
+    f (g a b) (h c d)
+    + long_name
+        * (other + 1)
+    - 2.
+"
+    );
+
+    let src =
+        "type Person := record (pub name: [char; 12]) (age: u32) (best_friends: [Person; 4]).";
+    assert_eq!(
+        check(src, WIDTH),
+        "\
+type Person := record
+    (pub name: [char; 12])
+    (age: u32)
+    (best_friends: [Person; 4]).
+"
+    );
+}
+
+#[test]
+fn comments() {
+    let src = "\
+// header
+
+#![doc(\"x\")]   // trailing
+type Point := record (x y: f64) // fields
+    // z
+    (z: f64).
+fun f (x: f64) : f64 begin // after begin
+    let y := x + // inside
+        1.0.
+
+
+    // before z
+    let z := match y with
+    | 1.0 => 2.0 // arm
+    // after arms
+    end.
+    z
+    // end of block
+end
+// last
+";
+    assert_eq!(
+        check(src, WIDTH),
+        "\
+// header
+
+#![doc(\"x\")] // trailing
+type Point := record
+    (x y: f64) // fields
+    // z
+    (z: f64).
+fun f (x: f64) : f64 begin
+    // after begin
+    let y := x + 1.0.
+    // inside
+
+    // before z
+    let z := match y with
+    | 1.0 => 2.0 // arm
+    // after arms
+    end.
+    z
+    // end of block
+end
+// last
+"
+    );
+}
+
+#[test]
+fn block_statements() {
+    // without their `.`, these would be the value of the block or be
+    // applied to what follows
+    let src = "\
+fun f begin
+    if a then b. end.
+    (x, y).
+    loop end.
+    -x
+end
+fun g begin
+    if a then b. end.
+end
+";
+    assert_eq!(check(src, WIDTH), src);
+    let src = "fun f begin if a then b. end. let x := 1. loop end. return end";
+    assert_eq!(
+        check(src, WIDTH),
+        "\
+fun f begin
+    if a then b. end
+    let x := 1.
+    loop end
+    return
+end
+"
+    );
+}
+
+#[test]
+fn escapes() {
+    // raw control chars are escaped, and the escapes read back to them
+    let src = "let c := '\u{1}'.\nlet s := \"a\u{7f}\tb\".\n";
+    assert_eq!(
+        check(src, WIDTH),
+        "let c := '\\u{1}'.\nlet s := \"a\\u{7f}\\tb\".\n"
+    );
+    let src = "let c := '\\u{1F600}'.\nlet q := '\\''.\n";
+    assert_eq!(
+        check(src, WIDTH),
+        "let c := '\u{1F600}'.\nlet q := '\\''.\n"
+    );
+}
+
+#[test]
+fn examples() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/points.l1");
+    check(&fs::read_to_string(path).unwrap(), WIDTH);
+}
+
+fn random_body(rng: &mut Rng, depth: usize) -> String {
+    let mut body = String::new();
+    for _ in 0..rng.below(5) {
+        let stmt = match if depth == 0 { 0 } else { rng.below(5) } {
+            0 => format!(
+                "let {} : {} := {}.",
+                rng.pattern(depth),
+                rng.ty(depth),
+                rng.expr(depth)
+            ),
+            1 => format!("{} <- {}.", rng.expr(depth), rng.expr(depth)),
+            2 => format!("loop {} end", random_body(rng, depth - 1)),
+            3 => format!(
+                "if {} then {} else {} end",
+                rng.expr(depth),
+                random_body(rng, depth - 1),
+                random_body(rng, depth - 1)
+            ),
+            _ => format!("{}.", rng.expr(depth)),
+        };
+        body.push_str(&stmt);
+        body.push('\n');
+    }
+    if rng.below(2) == 0 {
+        body.push_str(&rng.expr(depth));
+    }
+    body
+}
+
+#[test]
+fn random_sources() {
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    for _ in 0..300 {
+        let depth = rng.below(5);
+        let src = format!(
+            "let C : {} := {}.\nfun f (x: {}) begin\n{}\nend\n",
+            rng.ty(depth),
+            rng.expr(depth),
+            rng.ty(depth),
+            random_body(&mut rng, depth)
+        );
+        for width in [10, 40, WIDTH] {
+            check(&src, width);
+        }
+    }
+}
+
+#[test]
+fn fmt_command() {
+    let dir = std::env::temp_dir().join(format!("melange-fmt-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("a.l1");
+    fs::write(&path, "type A:=|X|Y.").unwrap();
+
+    let fmt = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_melange"))
+            .arg("fmt")
+            .args(args)
+            .arg(&path)
+            .status()
+            .unwrap()
+            .success()
+    };
+    assert!(!fmt(&["--check"]));
+    assert!(fmt(&[]));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "type A :=\n    | X\n    | Y.\n"
+    );
+    assert!(fmt(&["--check"]));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,125 @@
+use melange::{
+    ast::{
+        json::{from_json, to_json},
+        Expr, SourceFile, Ty,
+    },
+    json::{Error, Json},
+    parser::parse_str,
+};
+
+const SRC: &str = "\
+#![lint(deny [a, b])]
+#[derive(Debug, Clone)] #[copy]
+pub type Point := record #[serialize] (pub x y: f64) (z: [u8; 4 * 4]).
+type State := | Normal | #[default] Accepting (raw Point, &mut i32).
+type F := f64 -> Point -> (i32, ! -> u8) -> !.
+let N : u32 := 340282366920938463463374607431768211455.
+impl Point
+    #[inline]
+    pub fun new (x y: f64) : Point := Point x y.
+    fun translate (&mut self) (by: Point) begin
+        self~x <- by~x.
+    end
+end
+fun f (x: f64) : f64 -> Point begin
+    let Point (mut a) _ := p.
+    let h := \"tab\\there \\\"q\\\" \\u{e9}\".
+    let c := 2.5i + 1.5f32 + 'é' + '\\n' + -128i8 + 3f64.
+    let add := (x y: i32) (z: u8) => x + y + z.
+    match x with | 'c' => 1 | \"s\" => 2 | (Point 0.0 y, [_]) => 3 end.
+    when | x < 0.0 => 0.0 | x = 8 => begin print x. end else 8.0 end.
+    for (a, b) in y do continue. end
+    loop if s is Shape~Circle c r then break. elif 2 < 3 then () end end
+    return (x: f64) => vp x.
+end
+module C begin
+    #![no_mut]
+    module D begin end
+end
+module A.
+import root~B~PointB.
+import Sub1~Point as Point2D.
+import Sub2~*.
+";
+
+#[test]
+fn round_trip() {
+    let file: SourceFile = parse_str(SRC).unwrap();
+    let json = to_json(&file, SRC);
+
+    for text in [json.to_string(), format!("{json:#}")] {
+        let parsed = Json::parse(&text).unwrap();
+        assert_eq!(parsed, json);
+        let back: SourceFile = from_json(&parsed).unwrap();
+        assert_eq!(back, file);
+    }
+}
+
+#[test]
+fn lines_and_columns() {
+    let src = "let A := 1.\nlet S := 'é' + 2.\n";
+    let file: SourceFile = parse_str(src).unwrap();
+    let json = to_json(&file, src);
+
+    let def = json.get("items").unwrap().as_array().unwrap()[1]
+        .get("kind")
+        .and_then(|kind| kind.get("def"))
+        .unwrap();
+    assert_eq!(
+        def.get("name").unwrap().get("name"),
+        Some(&Json::String("S".into()))
+    );
+
+    let rhs = def
+        .get("value")
+        .and_then(|value| value.get("kind"))
+        .and_then(|kind| kind.get("rhs"))
+        .and_then(|rhs| rhs.get("span"))
+        .unwrap();
+    let get = |key| rhs.get(key).unwrap().as_u32().unwrap();
+    assert_eq!(get("start"), 28);
+    assert_eq!((get("line"), get("col")), (2, 16));
+    assert_eq!((get("end_line"), get("end_col")), (2, 17));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(Json::parse("{\"a\": }"), Err(Error::Syntax(6)));
+    assert_eq!(Json::parse("[1, 2] 3"), Err(Error::Syntax(7)));
+
+    let expr: Expr = parse_str("f x").unwrap();
+    let json = to_json(&expr, "f x");
+    assert_eq!(
+        from_json::<Ty>(&json),
+        Err(Error::UnexpectedNode("Expr".into()))
+    );
+
+    let Json::Object(mut fields) = json else {
+        panic!("nodes are objects")
+    };
+    fields.retain(|(key, _)| key != "kind");
+    assert_eq!(
+        from_json::<Expr>(&Json::Object(fields)),
+        Err(Error::MissingField("kind".into()))
+    );
+}
+
+#[test]
+fn strings() {
+    let s = Json::String("a\"\\\n\u{1}é😀".into());
+    assert_eq!(s.to_string(), "\"a\\\"\\\\\\n\\u0001é😀\"");
+    assert_eq!(Json::parse(&s.to_string()), Ok(s));
+    assert_eq!(
+        Json::parse("\"\\ud83d\\ude00\\u00e9\""),
+        Ok(Json::String("😀é".into()))
+    );
+    assert!(Json::parse("\"\\ud83d\\u0041\"").is_err());
+
+    // only C0 controls must be escaped
+    assert_eq!(
+        Json::parse("\"\u{7f}\u{85}\""),
+        Ok(Json::String("\u{7f}\u{85}".into()))
+    );
+    assert!(Json::parse("\"\u{1f}\"").is_err());
+    assert!(Json::parse("\"\t\"").is_err());
+}
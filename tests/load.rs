@@ -0,0 +1,145 @@
+use melange::{
+    ast::{visit, Expr, NodeId, SourceFile, StructuralEq, Ty, Visitor},
+    load::{load, Error, Program},
+    parser::parse_str,
+    resolve::{self, resolve},
+    span::Span,
+};
+
+use std::{collections::HashSet, fs, path::PathBuf};
+
+/// Writes the files in a fresh directory, and returns it.
+fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("melange-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (path, src) in files {
+        fs::write(dir.join(path), src).unwrap();
+    }
+    dir
+}
+
+/// The name of the file a span lies in, and its text.
+fn locate(program: &Program, span: Span) -> (String, &str) {
+    let file = program.file_at(span.start());
+    let start = (span.start() - file.offset) as usize;
+    let name = file.path.file_name().unwrap().to_string_lossy();
+    (
+        name.into_owned(),
+        &file.src[start..start + span.len() as usize],
+    )
+}
+
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl<'ast> Visitor<'ast> for Ids {
+    fn visit_ty(&mut self, ty: &'ast Ty) {
+        self.0.push(ty.id);
+        visit::walk_ty(self, ty);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.0.push(expr.id);
+        visit::walk_expr(self, expr);
+    }
+}
+
+#[test]
+fn module_tree() {
+    let dir = write_files(
+        "load",
+        &[
+            (
+                "main.l1",
+                "module A.\nmodule C begin\n    module B.\nend\nimport root~C~B~PointB.\nfun f (p: PointB) := p.\n",
+            ),
+            ("A.l1", "pub type Point := record (x y: f64).\n"),
+            (
+                "B.l1",
+                "#![doc(\"b\")]\npub type PointB := root~A~Point.\nfun g := Missing.\n",
+            ),
+        ],
+    );
+    let program = load(&dir.join("main.l1")).unwrap();
+    assert!(program.errors.is_empty(), "{:?}", program.errors);
+
+    let inline: SourceFile = parse_str(
+        "\
+module A begin pub type Point := record (x y: f64). end
+module C begin
+    module B begin
+        #![doc(\"b\")]
+        pub type PointB := root~A~Point.
+        fun g := Missing.
+    end
+end
+import root~C~B~PointB.
+fun f (p: PointB) := p.
+",
+    )
+    .unwrap();
+    assert!(program.file.structurally_eq(&inline), "{}", program.file);
+
+    let names: Vec<_> = program
+        .files
+        .iter()
+        .map(|file| file.path.strip_prefix(&dir).unwrap().to_str().unwrap())
+        .collect();
+    assert_eq!(names, ["main.l1", "A.l1", "B.l1"]);
+
+    let mut ids = Ids::default();
+    ids.visit_source_file(&program.file);
+    assert_eq!(ids.0.iter().collect::<HashSet<_>>().len(), ids.0.len());
+
+    let errors = resolve(&program.file).errors;
+    let [resolve::Error::Unresolved(span)] = errors[..] else {
+        panic!("{errors:?}");
+    };
+    assert_eq!(locate(&program, span), ("B.l1".to_string(), "Missing"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn errors() {
+    let dir = write_files(
+        "load-errors",
+        &[
+            (
+                "main.l1",
+                "module A.\nmodule Nope.\nmodule A.\nmodule main.\nmodule Bad.\n",
+            ),
+            ("A.l1", "module A.\n"),
+            ("Bad.l1", "type := .\n"),
+        ],
+    );
+    let program = load(&dir.join("main.l1")).unwrap();
+    let errors: Vec<_> = program
+        .errors
+        .iter()
+        .map(|error| match error {
+            Error::Missing(span, path) => format!(
+                "{:?} missing {}",
+                locate(&program, *span),
+                path.strip_prefix(&dir).unwrap().display()
+            ),
+            Error::Read(span, _, kind) => format!("{:?} {kind}", locate(&program, *span)),
+            Error::Parse(path, _) => format!("{} doesn't parse", path.display()),
+            Error::Duplicate(span) => format!("{:?} duplicate", locate(&program, *span)),
+            Error::Cycle(span) => format!("{:?} cycle", locate(&program, *span)),
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            r#"("A.l1", "module A.") cycle"#.to_string(),
+            r#"("main.l1", "module Nope.") missing Nope.l1"#.to_string(),
+            r#"("main.l1", "module A.") duplicate"#.to_string(),
+            r#"("main.l1", "module main.") cycle"#.to_string(),
+            format!("{} doesn't parse", dir.join("Bad.l1").display()),
+        ]
+    );
+
+    assert!(load(&dir.join("missing.l1")).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,77 @@
+use melange::{
+    ast::{visit, Expr, NodeId, NodeMap, Param, Pattern, SourceFile, Ty, Visitor},
+    parser::parse_str,
+};
+
+use std::collections::HashSet;
+
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl<'ast> Visitor<'ast> for Ids {
+    fn visit_param(&mut self, param: &'ast Param) {
+        self.0.push(param.id);
+    }
+
+    fn visit_ty(&mut self, ty: &'ast Ty) {
+        self.0.push(ty.id);
+        visit::walk_ty(self, ty);
+    }
+
+    fn visit_pattern(&mut self, pat: &'ast Pattern) {
+        self.0.push(pat.id);
+        visit::walk_pattern(self, pat);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.0.push(expr.id);
+        visit::walk_expr(self, expr);
+    }
+}
+
+const SRC: &str = "\
+type Point := record (x y z: f64).
+fun dist (a b: Point) : f64 begin
+    let (dx, dy) := (a~x - b~x, a~y - b~y).
+    dx * dx + dy * dy
+end
+";
+
+#[test]
+fn ids_are_unique() {
+    let file: SourceFile = parse_str(SRC).unwrap();
+    let mut ids = Ids::default();
+    ids.visit_source_file(&file);
+
+    let unique: HashSet<_> = ids.0.iter().collect();
+    assert_eq!(unique.len(), ids.0.len());
+    // the source file is finished last
+    assert!(ids.0.iter().all(|&id| id < file.id));
+}
+
+#[test]
+fn ids_are_stable() {
+    let a: SourceFile = parse_str(SRC).unwrap();
+    let b: SourceFile = parse_str(SRC).unwrap();
+    let (mut ids_a, mut ids_b) = (Ids::default(), Ids::default());
+    ids_a.visit_source_file(&a);
+    ids_b.visit_source_file(&b);
+    assert_eq!(ids_a.0, ids_b.0);
+}
+
+#[test]
+fn side_table() {
+    let file: SourceFile = parse_str(SRC).unwrap();
+    let mut ids = Ids::default();
+    ids.visit_source_file(&file);
+
+    let mut table = NodeMap::new();
+    for (i, &id) in ids.0.iter().enumerate() {
+        table.insert(id, i);
+    }
+    assert_eq!(table.len(), ids.0.len());
+    assert_eq!(table[ids.0[3]], 3);
+    assert_eq!(table.remove(ids.0[3]), Some(3));
+    assert!(!table.contains(ids.0[3]));
+    assert!(table.get(file.id).is_none());
+}
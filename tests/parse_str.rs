@@ -0,0 +1,292 @@
+use melange::{
+    ast::{sexp, Expr, ItemKind, Pattern, SourceFile, StructuralEq, Ty},
+    parser::{parse_str, Error},
+};
+
+use std::fmt::Display;
+
+/// Parses `src`, checks that its s-expression reads back to the same tree,
+/// and returns the s-expression.
+fn round_trip<T>(src: &str) -> String
+where
+    T: melange::parser::Parse + sexp::FromSexp + StructuralEq + Display,
+{
+    let node: T = parse_str(src).unwrap_or_else(|err| panic!("couldn't parse {src}: {err:?}"));
+    let text = node.to_string();
+    let read: T = sexp::read(&text).unwrap_or_else(|err| panic!("couldn't read {text}: {err:?}"));
+    assert!(node.structurally_eq(&read), "{text} reads back differently");
+    text
+}
+
+#[test]
+fn unit() {
+    assert_eq!(parse_str::<Ty>("()").unwrap().to_string(), "(tup)");
+    assert_eq!(
+        parse_str::<Ty>("() -> ()").unwrap().to_string(),
+        "(fn (tup) (tup))"
+    );
+    assert_eq!(parse_str::<Expr>("()").unwrap().to_string(), "(tup)");
+    assert_eq!(parse_str::<Pattern>("()").unwrap().to_string(), "(tup)");
+}
+
+#[test]
+fn trailing_tokens() {
+    match parse_str::<Ty>("i32 i32") {
+        Err(Error::TrailingTokens(span)) => assert_eq!(span.start(), 4),
+        res => panic!("expected trailing tokens, got {res:?}"),
+    }
+    assert!(parse_str::<Expr>("1 + 2").is_ok());
+}
+
+#[test]
+fn empty_file() {
+    let file = parse_str::<SourceFile>("").unwrap();
+    assert_eq!(file.to_string(), "(file)");
+}
+
+#[test]
+fn empty_nodes() {
+    let src = "\
+type Unit := ().
+type Empty := record.
+fun f (x: ()) : () := begin end.
+fun g := (() , ()).
+module M begin end
+";
+    let file = parse_str::<SourceFile>(src).unwrap();
+    assert!(!file.to_string().is_empty());
+}
+
+#[test]
+fn patterns() {
+    let cases = [
+        ("_", "(wild)"),
+        ("x", "(bind x)"),
+        ("mut x", "(bind mut x)"),
+        // a lone name is a binding, whether it names a variant is up to
+        // name resolution
+        ("None", "(bind None)"),
+        ("42", "(lit 42)"),
+        ("'c'", "(lit 'c')"),
+        ("-1", "(neg (lit 1))"),
+        ("-128i8", "(neg (lit 128i8))"),
+        ("(x)", "(bind x)"),
+        ("(x, _, 1.5)", "(tup (bind x) (wild) (lit 1.5))"),
+        ("[]", "(array)"),
+        ("[a, mut b]", "(array (bind a) (bind mut b))"),
+        ("root~A~B", "(ctor (path root A B))"),
+        (
+            "Point (mut x) y",
+            "(ctor (path Point) (bind mut x) (bind y))",
+        ),
+        (
+            "Shape~Circle (Point 0.0 y) r",
+            "(ctor (path Shape Circle) (ctor (path Point) (lit 0.0) (bind y)) (bind r))",
+        ),
+        (
+            "Point -1 -2.5",
+            "(ctor (path Point) (neg (lit 1)) (neg (lit 2.5)))",
+        ),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(round_trip::<Pattern>(src), expected);
+    }
+    assert!(round_trip::<Expr>("match x with | -1 => 0 | _ => 1 end").contains("(neg (lit 1))"));
+
+    for src in [
+        "mut",
+        "mut A~B",
+        "(x, ",
+        "[a b",
+        "-x",
+        "-'c'",
+        "-128i8 -129i8",
+    ] {
+        assert!(parse_str::<Pattern>(src).is_err(), "{src} parsed");
+    }
+}
+
+#[test]
+fn expressions() {
+    let cases = [
+        ("1 + 2 * 3", "(+ (lit 1) (* (lit 2) (lit 3)))"),
+        ("a - b - c", "(- (- (path a) (path b)) (path c))"),
+        ("a < b + 1", "(< (path a) (+ (path b) (lit 1)))"),
+        ("-x * y", "(* (neg (path x)) (path y))"),
+        (
+            "f x (g y)",
+            "(call (path f) (path x) (call (path g) (path y)))",
+        ),
+        ("p~x", "(path p x)"),
+        ("Point 1.0 2.0", "(call (path Point) (lit 1.0) (lit 2.0))"),
+        (
+            "Shape~Circle (Point 0.0 0.0) 1.0",
+            "(call (path Shape Circle) (call (path Point) (lit 0.0) (lit 0.0)) (lit 1.0))",
+        ),
+        (
+            "if a then 1 elif b then 2 else 3 end",
+            "(if (branch (path a) (block (lit 1))) (branch (path b) (block (lit 2))) \
+             (else (block (lit 3))))",
+        ),
+        (
+            "if s is Shape~Circle c r then r else 0.0 end",
+            "(if (branch (is (path s) (ctor (path Shape Circle) (bind c) (bind r))) \
+             (block (path r))) (else (block (lit 0.0))))",
+        ),
+        (
+            "match (s, o) with | (Shape~Circle c r, _) => r | _ => 0.0 end",
+            "(match (tup (path s) (path o)) \
+             (arm (tup (ctor (path Shape Circle) (bind c) (bind r)) (wild)) (path r)) \
+             (arm (wild) (lit 0.0)))",
+        ),
+        (
+            "when | x < 0 => -1 | x > 0 => 1 else 0 end",
+            "(when (arm (< (path x) (lit 0)) (neg (lit 1))) (arm (> (path x) (lit 0)) (lit 1)) \
+             (else (block (lit 0))))",
+        ),
+        ("loop f x end", "(loop (block (call (path f) (path x))))"),
+        (
+            "for (i, x) in xs do f i x end",
+            "(for (tup (bind i) (bind x)) (path xs) \
+             (block (call (path f) (path i) (path x))))",
+        ),
+        (
+            "(x: f64) (y: f64) => x + y",
+            "(lambda (param x (path f64)) (param y (path f64)) (+ (path x) (path y)))",
+        ),
+        (
+            "begin let mut x := 1. x <- x + 1. x end",
+            "(block (let (bind mut x) (lit 1)) (stmt (assign (path x) (+ (path x) (lit 1)))) \
+             (path x))",
+        ),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(round_trip::<Expr>(src), expected);
+    }
+
+    for src in [
+        "if a then 1",
+        "match x with | _ end",
+        "for x in xs f x end",
+        "(x: f64) =>",
+    ] {
+        assert!(parse_str::<Expr>(src).is_err(), "{src} parsed");
+    }
+}
+
+#[test]
+fn visibility() {
+    let src = "\
+pub type Point := record (pub x y: f64) (z: f64).
+pub let N : u32 := 4.
+impl Point pub fun norm (self) : f64 := self~x. end
+pub fun f (x: f64) := x.
+";
+    let file = round_trip::<SourceFile>(src);
+    assert_eq!(file.matches("pub").count(), 5, "{file}");
+
+    // parameters aren't visible outside of their function anyway
+    for src in [
+        "fun f (pub x: f64) := x.",
+        "fun f := (pub x: f64) => x.",
+        "impl Point fun f (self) (pub x: f64) := x. end",
+    ] {
+        assert!(parse_str::<SourceFile>(src).is_err(), "{src} parsed");
+    }
+}
+
+#[test]
+fn method_attributes() {
+    let src = "impl P #[inline] #[doc(\"x\")] pub fun f (self) := 1. fun g := 2. end";
+    let file = round_trip::<SourceFile>(src);
+    assert!(
+        file.contains("(method (attr (path inline)) (attr (path doc)"),
+        "{file}"
+    );
+
+    let file: SourceFile = parse_str(src).unwrap();
+    let ItemKind::Impl(imp) = &file.items[0].kind else {
+        panic!("{file}");
+    };
+    let [f, g] = &imp.methods[..] else {
+        panic!("{file}");
+    };
+    assert_eq!(f.attrs.len(), 2);
+    assert_eq!(f.span.start(), 7);
+    assert_eq!(f.span.end(), f.fn_def.span.end());
+    assert!(g.attrs.is_empty());
+    assert_eq!(g.span, g.fn_def.span);
+
+    // inner attributes belong to modules and files
+    assert!(parse_str::<SourceFile>("impl P #![inline] fun f := 1. end").is_err());
+    assert!(parse_str::<SourceFile>("impl P #[inline] end").is_err());
+}
+
+#[test]
+fn literals() {
+    let cases = [
+        (r"'\u{1F600}'", "(lit '😀')"),
+        (r"'\u{41}'", "(lit 'A')"),
+        (r"'\''", r"(lit '\'')"),
+        (r#""a\"b\u{1}""#, r#"(lit "a\"b\u{1}")"#),
+        ("-128i8", "(neg (lit 128i8))"),
+        ("255u8", "(lit 255u8)"),
+        ("1.5f32", "(lit 1.5f32)"),
+        ("2.5i", "(lit 0.0+2.5i)"),
+        // underscores group digits
+        ("1_000_000", "(lit 1000000)"),
+        ("1_u8", "(lit 1u8)"),
+        ("0.000_1f64", "(lit 0.0001f64)"),
+        ("2_0.5_i", "(lit 0.0+20.5i)"),
+    ];
+    for (src, expected) in cases {
+        assert_eq!(round_trip::<Expr>(src), expected);
+    }
+}
+
+#[test]
+fn literal_errors() {
+    let error = |src: &str| {
+        let err = parse_str::<SourceFile>(src).unwrap_err();
+        let (name, span) = match err {
+            Error::LiteralOutOfRange(span) => ("out of range", span),
+            Error::InvalidSuffix(span) => ("invalid suffix", span),
+            Error::InvalidEscape(span) => ("invalid escape", span),
+            Error::InvalidToken(span) => ("invalid token", span),
+            err => panic!("{src}: {err:?}"),
+        };
+        format!(
+            "{name} {}",
+            &src[span.start() as usize..span.end() as usize]
+        )
+    };
+
+    let cases = [
+        ("let x := 300u8.", "out of range 300u8"),
+        ("let x := -129i8.", "out of range 129i8"),
+        (
+            "let x := 99999999999999999999999999999999999999999.",
+            "out of range",
+        ),
+        ("let x := 1x.", "invalid suffix 1x"),
+        ("let x := 1.0u8.", "invalid suffix 1.0u8"),
+        (r"let x := '\q'.", r"invalid escape '\q'"),
+        (r"let x := '\u{zz}'.", r"invalid escape '\u{zz}'"),
+        (
+            r#"let x := "\u{110000}"."#,
+            r#"invalid escape "\u{110000}""#,
+        ),
+        ("let x := 'ab'.", "invalid token 'ab'"),
+        (r"let x := '\u{1F600.", r"invalid token '\u{1F600."),
+        ("let x := \"abc.", "invalid token \"abc."),
+        ("#[doc('ab')] fun f := 1.", "invalid token 'ab'"),
+        ("fun f := 1. 'ab'", "invalid token 'ab'"),
+        // only ASCII digits make numbers
+        ("let x := １２.", "invalid token １２"),
+        ("let x := 1２.", "invalid token ２"),
+        ("let x := 1_000_u8 + 1_000u8.", "out of range 1_000_u8"),
+    ];
+    for (src, expected) in cases {
+        assert!(error(src).starts_with(expected), "{src}: {}", error(src));
+    }
+}
@@ -0,0 +1,211 @@
+use melange::{
+    ast::{
+        pretty::Printer, visit, Expr, Item, NodeId, Pattern, SourceFile, StructuralEq, Ty, Visitor,
+    },
+    parser::Error,
+    span::Span,
+    syntax::{self, Reparsed, ReparsedAst, TextEdit},
+};
+
+const SRC: &str = "\
+// points
+type Point := record (x y: f64).
+
+fun norm (p: Point) : f64 :=
+    p~x * p~x + p~y * p~y.
+
+module geometry begin
+    let ORIGIN := 0.
+    fun twice (x: f64) : f64 := x + x.
+end
+";
+
+/// The ids of items, types, patterns and expressions.
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl<'ast> Visitor<'ast> for Ids {
+    fn visit_item(&mut self, item: &'ast Item) {
+        self.0.push(item.id);
+        visit::walk_item(self, item);
+    }
+
+    fn visit_ty(&mut self, ty: &'ast Ty) {
+        self.0.push(ty.id);
+        visit::walk_ty(self, ty);
+    }
+
+    fn visit_pattern(&mut self, pat: &'ast Pattern) {
+        self.0.push(pat.id);
+        visit::walk_pattern(self, pat);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.0.push(expr.id);
+        visit::walk_expr(self, expr);
+    }
+}
+
+fn ids(file: &SourceFile) -> Vec<NodeId> {
+    let mut ids = Ids::default();
+    ids.visit_source_file(file);
+    ids.0
+}
+
+/// Applies `edit` both incrementally and by reparsing the whole file, checks
+/// that both trees and ASTs are the same, and that only an item was reparsed
+/// if `item` is set, leaving the ids of the other items alone.
+fn check_reparse(src: &str, edit: TextEdit, item: bool) -> String {
+    let mut next_id = NodeId::new(0);
+    let (root, mut file) = syntax::parse(src, &mut next_id).unwrap();
+    let first_new = next_id;
+    let start = usize::try_from(edit.span.start()).unwrap();
+    let end = start + usize::try_from(edit.span.len()).unwrap();
+    let text = format!("{}{}{}", &src[..start], edit.text, &src[end..]);
+
+    let Reparsed {
+        root: incremental,
+        ast,
+    } = syntax::reparse(&root, &edit, &mut next_id).unwrap();
+    let (full, full_file) = syntax::parse(&text, &mut NodeId::new(0)).unwrap();
+    assert_eq!(incremental.to_string(), text);
+    assert_eq!(incremental.green(), full.green());
+
+    let new_ids = match &ast {
+        ReparsedAst::Item { item, .. } => {
+            let mut ids = Ids::default();
+            ids.visit_item(item);
+            ids.0.len()
+        }
+        ReparsedAst::File(_) => ids(&full_file).len(),
+    };
+    assert_eq!(matches!(ast, ReparsedAst::Item { .. }), item, "{text}");
+    ast.apply(&mut file);
+
+    assert!(file.structurally_eq(&full_file), "{text}");
+    let printer = Printer::new(80).with_spans(&text);
+    assert_eq!(printer.print(&file), printer.print(&full_file));
+    let mut ids = ids(&file);
+    let kept = ids.iter().filter(|&&id| id < first_new).count();
+    assert_eq!(ids.len() - kept, new_ids);
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), kept + new_ids, "ids are shared");
+    assert!(ids.iter().all(|&id| id < next_id));
+    text
+}
+
+fn check(src: &str, edit: TextEdit) -> String {
+    check_reparse(src, edit, true)
+}
+
+fn at(src: &str, pat: &str) -> u32 {
+    u32::try_from(src.find(pat).unwrap()).unwrap()
+}
+
+#[test]
+fn edit_inside_fn() {
+    let start = at(SRC, "p~y * p~y");
+    check(SRC, TextEdit::new(Span::new(start, 9), "2.0 * p~y"));
+}
+
+#[test]
+fn edit_inside_type() {
+    let start = at(SRC, "x y");
+    check(SRC, TextEdit::new(Span::new(start + 2, 0), "z "));
+}
+
+#[test]
+fn edit_inside_nested_item() {
+    let start = at(SRC, "x + x");
+    check(SRC, TextEdit::new(Span::new(start, 5), "2.0 * x"));
+}
+
+#[test]
+fn edit_splitting_an_item() {
+    let start = at(SRC, " + p~y");
+    check_reparse(
+        SRC,
+        TextEdit::new(Span::new(start, 0), ". fun g := 1"),
+        false,
+    );
+}
+
+#[test]
+fn edit_across_items() {
+    let start = at(SRC, "f64).");
+    let end = at(SRC, "(p: Point)");
+    check_reparse(
+        SRC,
+        TextEdit::new(Span::new(start, end - start), "f64).\nfun f "),
+        false,
+    );
+}
+
+#[test]
+fn comment_running_past_an_item() {
+    let start = at(SRC, "p~y.");
+    check_reparse(SRC, TextEdit::new(Span::new(start + 3, 0), ". // y"), false);
+}
+
+#[test]
+fn successive_edits() {
+    let start = at(SRC, "ORIGIN");
+    let src = check(SRC, TextEdit::new(Span::new(start, 6), "ZERO"));
+    let start = at(&src, "norm");
+    check(&src, TextEdit::new(Span::new(start + 1, 2), "ab"));
+}
+
+#[test]
+fn invalid_edit() {
+    let (root, _) = syntax::parse(SRC, &mut NodeId::new(0)).unwrap();
+    let start = at(SRC, ":= record");
+    let edit = TextEdit::new(Span::new(start, 2), ":");
+    assert!(syntax::reparse(&root, &edit, &mut NodeId::new(0)).is_err());
+}
+
+#[test]
+fn edit_splitting_a_module() {
+    let src = "module m begin fun f := 1. end\nfun g := 2.\n";
+    let start = at(src, "1.");
+    check_reparse(
+        src,
+        TextEdit::new(Span::new(start, 2), "1. end module n begin"),
+        false,
+    );
+}
+
+#[test]
+fn half_typed_literals() {
+    let (root, _) = syntax::parse(SRC, &mut NodeId::new(0)).unwrap();
+    let start = at(SRC, "p~y * p~y");
+    for text in ["\"2.0", "'", "'\\u{1F6", "'ab'"] {
+        let edit = TextEdit::new(Span::new(start, 0), text);
+        match syntax::reparse(&root, &edit, &mut NodeId::new(0)) {
+            Err(Error::InvalidToken(span)) => assert_eq!(span.start(), start),
+            res => panic!("{text}: {:?}", res.map(|reparsed| reparsed.root)),
+        }
+    }
+
+    // a string closed by the edit
+    check(SRC, TextEdit::new(Span::new(start, 0), "f \"\" "));
+}
+
+#[test]
+fn ids_across_nodes() {
+    let mut next_id = NodeId::new(0);
+    let (root, file) = syntax::parse(SRC, &mut next_id).unwrap();
+    assert!(file.id < next_id);
+
+    let mut ids = Ids::default();
+    for node in root.children() {
+        let item: Item = node.to_ast(&mut next_id).unwrap();
+        ids.visit_item(&item);
+    }
+    ids.visit_source_file(&file);
+    let count = ids.0.len();
+    ids.0.sort();
+    ids.0.dedup();
+    assert_eq!(ids.0.len(), count);
+    assert!(ids.0.iter().all(|&id| id < next_id));
+}
@@ -0,0 +1,340 @@
+use melange::{
+    ast::{
+        visit::{self, Visitor},
+        Binding, ConstDef, Enum, FnDef, Module, NodeId, Param, Path, Receiver, SourceFile, TyDef,
+    },
+    interner,
+    parser::parse_str,
+    resolve::{resolve, Error, Res},
+    span::Span,
+};
+
+use std::collections::HashMap;
+
+/// Names the definitions of a file by their id, locals with their offset.
+#[derive(Default)]
+struct Names {
+    names: HashMap<NodeId, String>,
+    paths: Vec<&'static Path>,
+}
+
+impl Names {
+    fn add(&mut self, id: NodeId, name: &str) {
+        self.names.insert(id, name.to_string());
+    }
+
+    fn local(&mut self, id: NodeId, name: &str, span: Span) {
+        self.add(id, &format!("{name}@{}", span.start()));
+    }
+}
+
+impl Visitor<'static> for Names {
+    fn visit_ty_def(&mut self, ty_def: &'static TyDef) {
+        self.add(ty_def.id, interner::get(ty_def.name.name));
+        visit::walk_ty_def(self, ty_def);
+    }
+
+    fn visit_enum(&mut self, enum_: &'static Enum) {
+        for variant in &enum_.variants {
+            self.add(variant.id, interner::get(variant.name.name));
+        }
+        visit::walk_enum(self, enum_);
+    }
+
+    fn visit_fn_def(&mut self, fn_def: &'static FnDef) {
+        self.add(fn_def.id, interner::get(fn_def.name.name));
+        visit::walk_fn_def(self, fn_def);
+    }
+
+    fn visit_const_def(&mut self, const_def: &'static ConstDef) {
+        self.add(const_def.id, interner::get(const_def.name.name));
+        visit::walk_const_def(self, const_def);
+    }
+
+    fn visit_module(&mut self, module: &'static Module) {
+        self.add(module.id, interner::get(module.name.name));
+        visit::walk_module(self, module);
+    }
+
+    fn visit_param(&mut self, param: &'static Param) {
+        self.local(param.id, interner::get(param.name.name), param.span);
+    }
+
+    fn visit_binding(&mut self, binding: &'static Binding) {
+        self.local(binding.id, interner::get(binding.name.name), binding.span);
+    }
+
+    fn visit_receiver(&mut self, receiver: &'static Receiver) {
+        self.local(receiver.id, "self", receiver.span);
+    }
+
+    fn visit_path(&mut self, path: &'static Path) {
+        self.paths.push(path);
+    }
+}
+
+/// Resolves `src` and describes what each of its paths resolves to, in
+/// order, e.g. `A~x: local x@10 ~x` for a path whose last segment is a field.
+fn resolved(src: &str) -> (Vec<String>, Vec<Error>) {
+    let file: &'static SourceFile = Box::leak(Box::new(parse_str(src).unwrap()));
+    let mut names = Names::default();
+    names.add(file.id, "root");
+    names.visit_source_file(file);
+
+    let resolutions = resolve(file);
+    let described = names
+        .paths
+        .iter()
+        .map(|path| {
+            let text: Vec<_> = path
+                .segments
+                .iter()
+                .map(|segment| interner::get(segment.name))
+                .collect();
+            let Some(resolution) = resolutions.paths.get(path.id) else {
+                return format!("{}: -", text.join("~"));
+            };
+            let def = match resolution.res {
+                Res::Module(id) => format!("module {}", names.names[&id]),
+                Res::Ty(id) => format!("type {}", names.names[&id]),
+                Res::PrimTy(name) => format!("prim {}", interner::get(name)),
+                Res::PrimValue(name) => format!("prim {}", interner::get(name)),
+                Res::Variant(id) => format!("variant {}", names.names[&id]),
+                Res::Fn(id) => format!("fn {}", names.names[&id]),
+                Res::Const(id) => format!("const {}", names.names[&id]),
+                Res::Local(id) => format!("local {}", names.names[&id]),
+            };
+            let fields: String = text[resolution.len..]
+                .iter()
+                .map(|field| format!(" ~{field}"))
+                .collect();
+            format!("{}: {def}{fields}", text.join("~"))
+        })
+        .collect();
+    (described, resolutions.errors)
+}
+
+#[test]
+fn definitions_and_locals() {
+    let src = "\
+type Point := record (x y: f64).
+type Shape := | Circle (Point, f64) | Square (Point, Point).
+let N : u32 := 4.
+impl Point
+    fun new (x y: f64) : Point := Point x y.
+    fun norm (self) : f64 := self~x * self~y.
+end
+fun area (s: Shape) : f64 :=
+    match s with
+    | Shape~Circle (c, r) => begin let r := r * r. r end
+    | Shape~Square a b => area (Shape~Circle (a, N))
+    end.
+fun apply (f: f64 -> f64) (x: f64) := (y: f64) => f (x + y).
+";
+    let (paths, errors) = resolved(src);
+    assert_eq!(errors, []);
+    assert_eq!(
+        paths,
+        [
+            "f64: prim f64",
+            "Point: type Point",
+            "f64: prim f64",
+            "Point: type Point",
+            "Point: type Point",
+            "u32: prim u32",
+            "Point: type Point",
+            "f64: prim f64",
+            "Point: type Point",
+            "Point: type Point",
+            "x: local x@136",
+            "y: local y@138",
+            "f64: prim f64",
+            "self~x: local self@181 ~x",
+            "self~y: local self@181 ~y",
+            "Shape: type Shape",
+            "f64: prim f64",
+            "s: local s@228",
+            "Shape~Circle: variant Circle",
+            "r: local r@287",
+            "r: local r@287",
+            "r: local r@303",
+            "Shape~Square: variant Square",
+            "area: fn area",
+            "Shape~Circle: variant Circle",
+            "a: local a@340",
+            "N: const N",
+            "f64: prim f64",
+            "f64: prim f64",
+            "f64: prim f64",
+            "f64: prim f64",
+            "f: local f@394",
+            "x: local x@410",
+            "y: local y@422",
+        ]
+    );
+}
+
+#[test]
+fn modules_and_imports() {
+    let src = "\
+type Shape := | Circle (f64) | Dot.
+module Geo begin
+    import root~Shape~*.
+    import Sub~origin as o.
+    module Sub begin
+        pub fun origin := Dot.
+        fun up := Shape~Circle (o).
+    end
+    fun unit := Circle 1.0.
+end
+fun f := Geo~Sub~origin.
+fun g := Geo~o~x.
+import Geo~Sub.
+fun h := Sub~up.
+import Point~new.
+type Point := record (x y: f64).
+impl Point fun new := new. end
+";
+    let (paths, errors) = resolved(src);
+    assert_eq!(errors, []);
+    assert_eq!(
+        paths,
+        [
+            "f64: prim f64",
+            "root~Shape: type Shape",
+            "Sub~origin: fn origin",
+            "Dot: variant Dot",
+            "Shape~Circle: variant Circle",
+            "o: fn origin",
+            "Circle: variant Circle",
+            "Geo~Sub~origin: fn origin",
+            "Geo~o~x: fn origin ~x",
+            "Geo~Sub: module Sub",
+            "Sub~up: fn up",
+            "Point~new: fn new",
+            "f64: prim f64",
+            "Point: type Point",
+            "new: fn new",
+        ]
+    );
+}
+
+#[test]
+fn lone_names_in_patterns() {
+    let src = "\
+type E := | A | B (f64).
+import E~*.
+let Yes := true.
+fun f (e: E) (b: bool) : f64 :=
+    match e with
+    | A => 1.0
+    | B x => match b with | false => x | mut A => A end
+    end.
+fun g (e: E) := match e with | B => false | a => a end.
+";
+    let file: SourceFile = parse_str(src).unwrap();
+    let resolutions = resolve(&file);
+    let text = |span: Span| &src[span.start() as usize..span.end() as usize];
+    let errors: Vec<_> = resolutions
+        .errors
+        .iter()
+        .map(|error| match *error {
+            Error::Expected(span, what) => format!("{} isn't {what}", text(span)),
+            ref error => panic!("{error:?}"),
+        })
+        .collect();
+    assert_eq!(errors, ["B isn't a unit variant"]);
+
+    let (paths, _) = resolved(src);
+    assert_eq!(
+        paths,
+        [
+            "f64: prim f64",
+            "E: type E",
+            "true: prim true",
+            "E: type E",
+            "bool: prim bool",
+            "f64: prim f64",
+            "e: local e@61",
+            "B: variant B",
+            "b: local b@68",
+            "x: local x@126",
+            "A: local A@159",
+            "E: type E",
+            "e: local e@190",
+            "false: prim false",
+            "a: local a@227",
+        ]
+    );
+
+    let mut names = Names::default();
+    let file = Box::leak(Box::new(file));
+    names.visit_source_file(file);
+    let mut bindings: Vec<_> = names
+        .names
+        .iter()
+        .filter_map(|(id, name)| {
+            let res = match resolutions.bindings.get(*id)? {
+                Res::Variant(id) => format!("variant {}", names.names[id]),
+                Res::PrimValue(name) => format!("prim {}", interner::get(*name)),
+                res => panic!("{res:?}"),
+            };
+            Some(format!("{name}: {res}"))
+        })
+        .collect();
+    bindings.sort();
+    assert_eq!(bindings, ["A@109: variant A", "false@146: prim false"]);
+}
+
+#[test]
+fn errors() {
+    let src = "\
+type T := | A | A.
+fun T := x.
+fun f (x: T~A) : Geo := T~B.
+module a begin import root~b~x. end
+module b begin import root~a~x. end
+fun g (p: f) := match p with | f => 1 | g x => 2 end.
+impl f64 fun h := 1. end
+impl a fun h := 1. end
+";
+    let (_, errors) = resolved(src);
+    let text = |span: Span| &src[span.start() as usize..span.end() as usize];
+    let errors: Vec<_> = errors
+        .into_iter()
+        .map(|error| match error {
+            Error::Unresolved(span) => format!("unresolved {}", text(span)),
+            Error::Duplicate(span) => format!("duplicate {}", text(span)),
+            Error::ImportCycle(span) => format!("cycle {}", text(span)),
+            Error::Expected(span, what) => format!("{} isn't {what}", text(span)),
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            "duplicate A",
+            "duplicate T",
+            "f64 isn't a user-defined type",
+            "a isn't a type",
+            "cycle import root~b~x.",
+            "unresolved x",
+            "T~A isn't a type",
+            "unresolved Geo",
+            "unresolved B",
+            "f isn't a type",
+            "g isn't a constructor",
+        ]
+    );
+}
+
+#[test]
+fn examples() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/points.l1");
+    let src = std::fs::read_to_string(path).unwrap();
+    let (paths, errors) = resolved(&src);
+    assert!(paths.contains(&"Orientation~Direct: variant Direct".to_string()));
+    let [Error::Unresolved(span)] = errors[..] else {
+        panic!("{errors:?}");
+    };
+    assert_eq!(&src[span.start() as usize..span.end() as usize], "InDirect");
+}
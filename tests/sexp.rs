@@ -0,0 +1,155 @@
+use melange::{
+    ast::{
+        pretty::Printer,
+        sexp::{read, Error, FromSexp},
+        Expr, Pattern, SourceFile, StructuralEq, Ty,
+    },
+    parser::{parse_str, Parse},
+};
+
+use std::fmt::Display;
+
+mod common;
+
+use common::Rng;
+
+const SRC: &str = "\
+#![lint(deny [a, b], doc \"a (b) \\\"c\\\"\", 'x')]
+#[derive(Debug, Clone)] #[copy]
+pub type Point := record #[serialize] (pub x y: f64) (z: [u8; 4 * 4]).
+type State := | Normal | #[default] Accepting (raw Point, &mut i32) | Never (!).
+type F := f64 -> Point -> (i32, ! -> u8) -> ().
+let N : u32 := 340282366920938463463374607431768211455.
+pub let M := N.
+impl Point
+    pub fun new (x y: f64) : Point := Point x y.
+    fun translate (&mut self) (by: Point) begin
+        self~x <- by~x.
+    end
+    #[inline] #[must_use]
+    fun get (self) := self.
+end
+fun f (x: f64) : f64 -> Point begin
+    let Point (mut a) _ := *p.
+    let h : Str := \"tab\\there \\\"q\\\" \\u{e9} (x)\".
+    let c := 2.5i + 1.5f32 + '\\'' + '\\n' + -128i8 + 3f64 + 'é' + ')' + 255u8.
+    let add := (x y: i32) (z: u8) => x + y + z.
+    match x with | 'c' => 1 | \"s\" => 2 | (Point 0.0 y, [_, -1]) => 3 end.
+    when | x < 0.0 => 0.0 | x = 8 => begin print x. end else 8.0 end.
+    for (a, b) in y do continue. end
+    loop if s is Shape~Circle c r then break. elif 2 <= 3 then () else return. end end
+    f (&x) (&mut y) (raw z) (a /= b) (a >= b).
+    return (x: f64) => vp x.
+end
+module C begin
+    #![no_mut]
+    module D begin end
+end
+module A.
+import root~B~PointB.
+import Sub1~Point as Point2D.
+import Sub2~*.
+";
+
+/// Prints `node`, reads it back and checks that nothing changed.
+fn round_trip<T: Parse + FromSexp + StructuralEq + Display>(src: &str) {
+    let node: T = parse_str(src).unwrap_or_else(|err| panic!("couldn't parse {src}: {err:?}"));
+    let printed = node.to_string();
+    let read: T = read(&printed).unwrap_or_else(|err| panic!("couldn't read {printed}: {err:?}"));
+    assert!(node.structurally_eq(&read), "{src} read back differently");
+    assert_eq!(read.to_string(), printed);
+}
+
+#[test]
+fn source_file() {
+    round_trip::<SourceFile>(SRC);
+}
+
+#[test]
+fn expectations() {
+    let expr: Expr = parse_str("f x (y + 2)").unwrap();
+    let expected: Expr = read(
+        "(call (path f)
+              (path x)
+              (+ (path y) (lit 2)))",
+    )
+    .unwrap();
+    assert!(expr.structurally_eq(&expected));
+
+    let ty: Ty = parse_str("&(i32, [u8; 4])").unwrap();
+    let expected: Ty = read("(ref (tup (path i32) (array (path u8) (lit 4))))").unwrap();
+    assert!(ty.structurally_eq(&expected));
+    let other: Ty = read("(ref-mut (tup (path i32) (array (path u8) (lit 4))))").unwrap();
+    assert!(!ty.structurally_eq(&other));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(read::<Expr>("(call (path f)"), Err(Error::Syntax(0)));
+    assert_eq!(read::<Expr>("(lit \"abc)"), Err(Error::Syntax(5)));
+    assert_eq!(read::<Expr>("(path x))"), Err(Error::Syntax(8)));
+    assert_eq!(
+        read::<Expr>("(frobnicate x)"),
+        Err(Error::Expected(0, "an expression"))
+    );
+    assert_eq!(
+        read::<Ty>("(array (path u8))"),
+        Err(Error::Expected(0, "a node"))
+    );
+    assert_eq!(
+        read::<Pattern>("(bind x y)"),
+        Err(Error::Expected(8, "the end of the list"))
+    );
+    assert_eq!(
+        read::<Expr>("(lit 12u7)"),
+        Err(Error::Expected(5, "a literal"))
+    );
+}
+
+#[test]
+fn layout() {
+    let src = "fun f (x: f64) begin let p := Point x (x + 1.0). end";
+    let file: SourceFile = parse_str(src).unwrap();
+    assert_eq!(Printer::new(200).print(&file), file.to_string());
+    let printed = Printer::new(40).print(&file);
+    assert_eq!(
+        printed,
+        "\
+(file
+  (fun f
+    (param x (path f64))
+    (block
+      (let
+        (bind p)
+        (call
+          (path Point)
+          (path x)
+          (+ (path x) (lit 1.0)))))))"
+    );
+    let read: SourceFile = read(&printed).unwrap();
+    assert!(file.structurally_eq(&read));
+
+    let expr: Expr = parse_str("f\n  (x + 1)").unwrap();
+    assert_eq!(
+        Printer::new(50).with_spans("f\n  (x + 1)").print(&expr),
+        "\
+(call@1:1-2:9
+  (path@1:1-1:2 f)
+  (+@2:4-2:9 (path@2:4-2:5 x) (lit@2:8-2:9 1)))"
+    );
+}
+
+#[test]
+fn random_round_trips() {
+    let mut rng = Rng(0x9e3779b97f4a7c15);
+    for _ in 0..500 {
+        let depth = rng.below(5);
+        round_trip::<Expr>(&rng.expr(depth));
+        round_trip::<Ty>(&rng.ty(depth));
+        round_trip::<Pattern>(&rng.pattern(depth));
+
+        let expr: Expr = parse_str(&rng.expr(depth)).unwrap();
+        let read: Expr = read(&Printer::new(rng.below(40)).print(&expr)).unwrap();
+        assert!(expr.structurally_eq(&read));
+    }
+}
@@ -0,0 +1,72 @@
+use melange::{
+    ast::{BinOp, Expr, ExprKind, Ident, Lit, LitKind, NodeId, Path, StructuralEq, Ty, TyKind, P},
+    interner,
+    parser::parse_str,
+    span::Span,
+};
+
+use std::collections::HashSet;
+
+fn expr(kind: ExprKind) -> Expr {
+    Expr::new(NodeId::new(0), Span::default(), kind)
+}
+
+fn path(name: &str) -> Path {
+    Path::new(
+        NodeId::new(0),
+        Span::default(),
+        vec![Ident::new(Span::default(), interner::intern(name))],
+    )
+}
+
+fn float(value: f64) -> Expr {
+    expr(ExprKind::Lit(Lit::new(
+        Span::default(),
+        LitKind::Float {
+            value,
+            suffix: None,
+        },
+    )))
+}
+
+#[test]
+fn parsed_matches_hand_built() {
+    let parsed: Expr = parse_str("x * 2.5").unwrap();
+    let built = expr(ExprKind::Binary(
+        BinOp::Mul,
+        P::new(expr(ExprKind::Path(path("x")))),
+        P::new(float(2.5)),
+    ));
+    assert!(parsed.structurally_eq(&built));
+    // spans and ids still take part in `==`
+    assert_ne!(parsed, built);
+
+    let other = expr(ExprKind::Binary(
+        BinOp::Add,
+        P::new(expr(ExprKind::Path(path("x")))),
+        P::new(float(2.5)),
+    ));
+    assert!(!parsed.structurally_eq(&other));
+}
+
+#[test]
+fn spans_and_ids_are_ignored() {
+    let a: Ty = parse_str("&(i32, T)").unwrap();
+    let b: Ty = parse_str("&  ( i32 ,T )").unwrap();
+    assert!(a.structurally_eq(&b));
+    assert_ne!(a.span, b.span);
+
+    let c: Ty = parse_str("&mut (i32, T)").unwrap();
+    assert!(matches!(c.kind, TyKind::Ref { mutable: true, .. }));
+    assert!(!a.structurally_eq(&c));
+}
+
+#[test]
+fn eq_and_hash() {
+    let a: Expr = parse_str("f 1.5 'c' \"s\"").unwrap();
+    assert_eq!(a, a.clone());
+
+    let set: HashSet<Expr> = [a.clone(), a.clone(), float(0.5)].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&a));
+}
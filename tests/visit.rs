@@ -0,0 +1,77 @@
+use melange::{
+    ast::{mut_visit, visit, Expr, ExprKind, Ident, MutVisitor, SourceFile, Ty, Visitor},
+    interner,
+    parser::parse_str,
+};
+
+const SRC: &str = "\
+type Pair := record (a b: [f64; N]).
+fun f (x: Pair) : f64 :=
+    match x with
+    | (a, b) => g a b
+    end.
+module M begin
+    let N : u32 := 2 * 2.
+end
+";
+
+#[derive(Default)]
+struct Count {
+    tys: usize,
+    exprs: usize,
+    idents: Vec<&'static str>,
+}
+
+impl<'ast> Visitor<'ast> for Count {
+    fn visit_ty(&mut self, ty: &'ast Ty) {
+        self.tys += 1;
+        visit::walk_ty(self, ty);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        self.exprs += 1;
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_ident(&mut self, ident: &'ast Ident) {
+        self.idents.push(interner::get(ident.name));
+    }
+}
+
+#[test]
+fn visit_every_node() {
+    let file: SourceFile = parse_str(SRC).unwrap();
+    let mut count = Count::default();
+    count.visit_source_file(&file);
+
+    // `[f64; N]`, `f64`, `Pair`, `f64` and `u32`
+    assert_eq!(count.tys, 5);
+    // `N`, the match, `x`, `g a b`, `g`, `a`, `b`, `2 * 2`, `2`, `2`
+    assert_eq!(count.exprs, 10);
+    assert_eq!(
+        count.idents,
+        [
+            "Pair", "a", "b", "f64", "N", "f", "x", "Pair", "f64", "x", "a", "b", "g", "a", "b",
+            "M", "N", "u32"
+        ]
+    );
+}
+
+/// Swaps the operands of every binary expression.
+struct Swap;
+
+impl MutVisitor for Swap {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if let ExprKind::Binary(_, lhs, rhs) = &mut expr.kind {
+            std::mem::swap(lhs, rhs);
+        }
+        mut_visit::walk_expr(self, expr);
+    }
+}
+
+#[test]
+fn mut_visit_rewrites_in_place() {
+    let mut expr: Expr = parse_str("1 - (2 / x)").unwrap();
+    Swap.visit_expr(&mut expr);
+    assert_eq!(expr.to_string(), "(- (/ (path x) (lit 2)) (lit 1))");
+}